@@ -9,6 +9,7 @@ use std::time::Duration;
 
 // Example
 // The temporary file for interprogram synchronization.
+// (Unix: `cluFlock::path_lock::PathLock` also protects against the unlink race.)
 
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -0,0 +1,368 @@
+//! Append log shared by several processes (Unix only).
+//!
+//! `LockedAppender` writes records under an exclusive lock in batches,
+//! `LockedLogReader` reads complete records under a shared lock. A record
+//! torn by a crashed writer (the last one, shorter than its framing) is
+//! skipped by the reader and cut off by the next writer before appending.
+//! A record of full length with a wrong checksum is damaged, not torn: it is
+//! an `InvalidData` error wherever it is, nothing is cut off.
+//!
+//! Record framing:
+//! * `LengthPrefixed`: `len: u32 LE`, data, `FNV-1a 32 of data: u32 LE`,
+//! * `NewlineDelimited`: data (without `\n`), `\n`.
+//!
+//! ```rust
+//! use cluFlock::append_log::FsyncPolicy;
+//! use cluFlock::append_log::LockedAppender;
+//! use cluFlock::append_log::LockedLogReader;
+//! use cluFlock::append_log::RecordFraming;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let mut appender = LockedAppender::open("./test_file", RecordFraming::LengthPrefixed)?
+//! 		.fsync_policy(FsyncPolicy::Always);
+//! 	appender.append(b"first")?;
+//! 	appender.append(b"second")?;
+//! 	appender.flush()?; // one write under the lock
+//!
+//! 	for record in LockedLogReader::open("./test_file", RecordFraming::LengthPrefixed)? {
+//! 		println!("{:?}", record?);
+//! 	}
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::mode::Shared;
+use crate::ExclusiveFlock;
+use crate::FlockLock;
+use crate::SharedFlock;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+use std::vec::Vec;
+
+/// Framing of the records in the file.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RecordFraming {
+	/// Length, data and checksum, any data.
+	LengthPrefixed,
+	/// Data and `\n`, the data must not contain `\n`.
+	NewlineDelimited,
+}
+
+/// When the appended records are synced to disk.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FsyncPolicy {
+	/// Never, the system decides.
+	Never,
+	/// After each written batch.
+	Always,
+	/// After a written batch, if the interval has passed since the last sync.
+	Interval(Duration),
+}
+
+/// Writer of records, the records are written in batches under an exclusive lock.
+#[derive(Debug)]
+pub struct LockedAppender {
+	file: File,
+	framing: RecordFraming,
+	fsync_policy: FsyncPolicy,
+	batch_size: usize,
+	batch: Vec<u8>,
+	/// End of the complete records known to this writer.
+	valid_len: u64,
+	last_sync: Instant,
+}
+
+impl LockedAppender {
+	/// Default size of a batch in bytes.
+	pub const DEFAULT_BATCH_SIZE: usize = 8 * 1024;
+
+	/// Open (create) the log.
+	pub fn open(path: impl AsRef<Path>, framing: RecordFraming) -> Result<Self, IoError> {
+		let file = OpenOptions::new()
+			.read(true)
+			.append(true)
+			.create(true)
+			.open(path)?;
+
+		Ok(Self {
+			file,
+			framing,
+			fsync_policy: FsyncPolicy::Never,
+			batch_size: Self::DEFAULT_BATCH_SIZE,
+			batch: Vec::new(),
+			valid_len: 0,
+			last_sync: Instant::now(),
+		})
+	}
+
+	/// Set when the records are synced to disk.
+	#[inline]
+	pub fn fsync_policy(mut self, policy: FsyncPolicy) -> Self {
+		self.fsync_policy = policy;
+		self
+	}
+
+	/// Write the batch when it reaches `size` bytes (`0`, write each record).
+	#[inline]
+	pub fn batch_size(mut self, size: usize) -> Self {
+		self.batch_size = size;
+		self
+	}
+
+	/// Framing of the records.
+	#[inline(always)]
+	pub const fn framing(&self) -> RecordFraming {
+		self.framing
+	}
+
+	/// Add a record to the batch (the batch is written when it is full).
+	pub fn append(&mut self, record: &[u8]) -> Result<(), IoError> {
+		match self.framing {
+			RecordFraming::LengthPrefixed => {
+				let len = u32::try_from(record.len()).map_err(|_| {
+					IoError::new(IoErrorKind::InvalidInput, "the record is too long")
+				})?;
+				self.batch.extend_from_slice(&len.to_le_bytes());
+				self.batch.extend_from_slice(record);
+				self.batch.extend_from_slice(&fnv1a(record).to_le_bytes());
+			}
+			RecordFraming::NewlineDelimited => {
+				if record.contains(&b'\n') {
+					return Err(IoError::new(
+						IoErrorKind::InvalidInput,
+						"the record contains a newline",
+					));
+				}
+				self.batch.extend_from_slice(record);
+				self.batch.push(b'\n');
+			}
+		}
+
+		match self.batch.len() >= self.batch_size {
+			true => self.flush(),
+			false => Ok(()),
+		}
+	}
+
+	/// Write the batch under an exclusive lock (cutting off a torn record
+	/// of a crashed writer) and sync it according to the policy.
+	/// A damaged record (a wrong checksum) is an `InvalidData` error,
+	/// the batch is kept.
+	pub fn flush(&mut self) -> Result<(), IoError> {
+		if self.batch.is_empty() {
+			return Ok(());
+		}
+
+		let mut lock = ExclusiveFlock::wait_lock(&self.file).map_err(FlockError::into_err)?;
+		let len = lock.metadata()?.len();
+		if len != self.valid_len {
+			// Appended by other writers (or truncated): check their records.
+			let from = match self.valid_len < len {
+				true => self.valid_len,
+				false => 0,
+			};
+			let end = scan_end(&self.file, self.framing, from)?;
+			if end < len {
+				self.file.set_len(end)?;
+			}
+			self.valid_len = end;
+		}
+
+		lock.write_all(&self.batch)?;
+		self.valid_len += self.batch.len() as u64;
+		self.batch.clear();
+
+		let is_sync = match self.fsync_policy {
+			FsyncPolicy::Never => false,
+			FsyncPolicy::Always => true,
+			FsyncPolicy::Interval(interval) => self.last_sync.elapsed() >= interval,
+		};
+		if is_sync {
+			lock.sync_data()?;
+			self.last_sync = Instant::now();
+		}
+
+		lock.unlock()
+	}
+
+	/// Write the batch and sync the file to disk.
+	pub fn sync(&mut self) -> Result<(), IoError> {
+		self.flush()?;
+		self.file.sync_data()?;
+		self.last_sync = Instant::now();
+
+		Ok(())
+	}
+}
+
+impl Drop for LockedAppender {
+	#[inline]
+	fn drop(&mut self) {
+		let _e = self.flush();
+	}
+}
+
+/// Reader of complete records under a shared lock, the iteration ends at the end
+/// of the file or at a torn record (a damaged record is an `InvalidData` error).
+#[derive(Debug)]
+pub struct LockedLogReader {
+	lock: FlockLock<BufReader<File>, Shared>,
+	framing: RecordFraming,
+	is_torn: bool,
+	is_damaged: bool,
+}
+
+impl LockedLogReader {
+	/// Open the log and expect to get a shared lock.
+	pub fn open(path: impl AsRef<Path>, framing: RecordFraming) -> Result<Self, IoError> {
+		let file = BufReader::new(File::open(path)?);
+		let lock = SharedFlock::wait_lock(file).map_err(FlockError::into_err)?;
+
+		Ok(Self {
+			lock,
+			framing,
+			is_torn: false,
+			is_damaged: false,
+		})
+	}
+
+	/// Read the next complete record (after a damaged record, only `None`).
+	pub fn read_record(&mut self) -> Result<Option<Vec<u8>>, IoError> {
+		if self.is_damaged {
+			return Ok(None);
+		}
+
+		let record = read_record(&mut self.lock, self.framing).inspect_err(|e| {
+			self.is_damaged = e.kind() == IoErrorKind::InvalidData;
+		});
+		match record? {
+			Record::Complete(record, _len) => Ok(Some(record)),
+			Record::Torn => {
+				self.is_torn = true;
+				Ok(None)
+			}
+			Record::End => Ok(None),
+		}
+	}
+
+	/// The iteration has ended at a torn record.
+	#[inline(always)]
+	pub const fn is_torn(&self) -> bool {
+		self.is_torn
+	}
+
+	/// Release the lock.
+	#[inline]
+	pub fn unlock(self) -> Result<(), IoError> {
+		self.lock.unlock()
+	}
+}
+
+impl Iterator for LockedLogReader {
+	type Item = Result<Vec<u8>, IoError>;
+
+	#[inline]
+	fn next(&mut self) -> Option<Self::Item> {
+		self.read_record().transpose()
+	}
+}
+
+enum Record {
+	/// The record and its length in the file.
+	Complete(Vec<u8>, u64),
+	/// Incomplete record at the end of the file.
+	Torn,
+	End,
+}
+
+fn read_record(reader: &mut impl BufRead, framing: RecordFraming) -> Result<Record, IoError> {
+	match framing {
+		RecordFraming::LengthPrefixed => {
+			let mut len = [0; 4];
+			match read_full(reader, &mut len)? {
+				0 => return Ok(Record::End),
+				4 => {}
+				_ => return Ok(Record::Torn),
+			}
+			let len = u32::from_le_bytes(len) as u64;
+
+			// Do not trust the length of a damaged record with allocation.
+			let mut record = Vec::new();
+			if reader.take(len).read_to_end(&mut record)? as u64 != len {
+				return Ok(Record::Torn);
+			}
+			let mut checksum = [0; 4];
+			if read_full(reader, &mut checksum)? != 4 {
+				return Ok(Record::Torn);
+			}
+			if u32::from_le_bytes(checksum) != fnv1a(&record) {
+				// A torn record is short, a record of full length is damaged.
+				return Err(IoError::new(
+					IoErrorKind::InvalidData,
+					"damaged record in the log",
+				));
+			}
+
+			Ok(Record::Complete(record, 4 + len + 4))
+		}
+		RecordFraming::NewlineDelimited => {
+			let mut record = Vec::new();
+			match reader.read_until(b'\n', &mut record)? {
+				0 => Ok(Record::End),
+				len if record.last() == Some(&b'\n') => {
+					record.pop();
+					Ok(Record::Complete(record, len as u64))
+				}
+				_ => Ok(Record::Torn),
+			}
+		}
+	}
+}
+
+/// Read as many bytes as possible into `buf`, the number of bytes read.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize, IoError> {
+	let mut len = 0;
+	while len < buf.len() {
+		match reader.read(&mut buf[len..]) {
+			Ok(0) => break,
+			Ok(a) => len += a,
+			Err(e) if e.kind() == IoErrorKind::Interrupted => {}
+			Err(e) => return Err(e),
+		}
+	}
+
+	Ok(len)
+}
+
+/// End of the complete records starting from `from`.
+fn scan_end(file: &File, framing: RecordFraming, from: u64) -> Result<u64, IoError> {
+	let mut reader = BufReader::new(file);
+	reader.seek(SeekFrom::Start(from))?;
+
+	let mut end = from;
+	while let Record::Complete(_record, len) = read_record(&mut reader, framing)? {
+		end += len;
+	}
+
+	Ok(end)
+}
+
+/// FNV-1a 32, the checksum of a record.
+fn fnv1a(data: &[u8]) -> u32 {
+	data.iter().fold(0x811c_9dc5, |hash, a| {
+		(hash ^ *a as u32).wrapping_mul(0x0100_0193)
+	})
+}
@@ -0,0 +1,846 @@
+//! Lock backends selected at runtime, Unix only.
+//!
+//! By default `flock` is used for whole-file locks and POSIX `fcntl` for range locks.
+//! `BackendFile` sets all locks of the data stream through the selected `LockBackend`:
+//!
+//! 1. `FlockBackend`: `flock`, whole file only.
+//! 1. `PosixBackend`: POSIX `fcntl` (F_SETLK, F_SETLKW), locks belong to the process.
+//! 1. `OfdBackend`: OFD `fcntl` (F_OFD_SETLK, F_OFD_SETLKW), Linux only.
+//! 1. `AutoBackend`: OFD `fcntl` (if supported by the kernel) or POSIX `fcntl` for all locks.
+//!
+//! `LockBackendKind` selects one of them by value, for example from configuration:
+//!
+//! ```rust
+//! use cluFlock::backend::BackendFile;
+//! use cluFlock::backend::LockBackendKind;
+//! use cluFlock::ExclusiveFlock;
+//! use std::fs::File;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let backend: LockBackendKind = "flock".parse()?;
+//! 	let file = File::create("./file")?;
+//!
+//! 	let file_lock = ExclusiveFlock::wait_lock(BackendFile::with_backend(&file, backend))?;
+//! 	println!("{:?}", file_lock);
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::interrupt::FlockInterruptPolicy;
+use crate::interrupt::RetryInterrupted;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::FlockMode;
+use crate::mode::Shared;
+use crate::range::checker::FlockRangePNumBehChecker;
+use crate::range::pnum::FlockRangePNumBeh;
+use crate::range::FlockRange;
+use crate::sys::FlockRangePNum;
+use crate::sys::RawFilePtr;
+use crate::unlock::WaitFlockUnlock;
+use crate::unlock::WaitFlockUnlockRange;
+use crate::ExclusiveFlock;
+use crate::ExclusiveFlockRange;
+use crate::FlockLock;
+use crate::FlockRangeLock;
+use crate::SharedFlock;
+use crate::SharedFlockRange;
+use core::fmt::Debug;
+use core::ops::Deref;
+use core::ops::DerefMut;
+use core::str::FromStr;
+
+/// Range of the whole file in platform numbers.
+const FULL_PRANGE: (FlockRangePNum, FlockRangePNum) = (
+	<FlockRangePNum as FlockRangePNumBeh>::MIN,
+	<FlockRangePNum as FlockRangePNumBeh>::MAX,
+);
+
+/// System calls used to set and remove locks.
+pub trait LockBackend: Debug {
+	/// Set the lock of the range (A..=B) of the file,
+	/// `is_wait == false` returns `WouldBlock` instead of waiting.
+	fn lock(
+		&self,
+		ptr: RawFilePtr,
+		mode: FlockMode,
+		is_wait: bool,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError>;
+
+	/// Remove the lock of the range (A..=B) of the file.
+	///
+	/// # Safety
+	///
+	/// The lock must have been set by the same backend, and the guard
+	/// that owns it must not be used after this call.
+	unsafe fn unlock(
+		&self,
+		ptr: RawFilePtr,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError>;
+}
+
+impl<B> LockBackend for &B
+where
+	B: LockBackend + ?Sized,
+{
+	#[inline(always)]
+	fn lock(
+		&self,
+		ptr: RawFilePtr,
+		mode: FlockMode,
+		is_wait: bool,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		B::lock(self, ptr, mode, is_wait, prange)
+	}
+
+	#[inline(always)]
+	unsafe fn unlock(
+		&self,
+		ptr: RawFilePtr,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		B::unlock(self, ptr, prange)
+	}
+}
+
+/// `flock`, only whole-file locks, a range lock returns `InvalidInput`.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FlockBackend;
+
+impl FlockBackend {
+	#[inline]
+	fn check_prange(prange: (FlockRangePNum, FlockRangePNum)) -> Result<(), IoError> {
+		match prange == FULL_PRANGE {
+			true => Ok(()),
+			false => Err(IoError::new(
+				IoErrorKind::InvalidInput,
+				"flock backend does not support range locks",
+			)),
+		}
+	}
+}
+
+impl LockBackend for FlockBackend {
+	#[inline]
+	fn lock(
+		&self,
+		ptr: RawFilePtr,
+		mode: FlockMode,
+		is_wait: bool,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		Self::check_prange(prange)?;
+
+		crate::sys::backend_flock(ptr, Some(mode), is_wait)
+	}
+
+	#[inline]
+	unsafe fn unlock(
+		&self,
+		ptr: RawFilePtr,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		Self::check_prange(prange)?;
+
+		crate::sys::backend_flock(ptr, None, true)
+	}
+}
+
+/// POSIX `fcntl` (F_SETLK, F_SETLKW), locks belong to the process and
+/// are released when any descriptor of the file is closed in it.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PosixBackend;
+
+impl LockBackend for PosixBackend {
+	#[inline]
+	fn lock(
+		&self,
+		ptr: RawFilePtr,
+		mode: FlockMode,
+		is_wait: bool,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		crate::sys::backend_posix_fcntl(ptr, Some(mode), is_wait, prange)
+	}
+
+	#[inline]
+	unsafe fn unlock(
+		&self,
+		ptr: RawFilePtr,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		crate::sys::backend_posix_fcntl(ptr, None, true, prange)
+	}
+}
+
+/// OFD `fcntl` (F_OFD_SETLK, F_OFD_SETLKW), locks belong to the open file description.
+#[cfg(target_os = "linux")]
+#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OfdBackend;
+
+#[cfg(target_os = "linux")]
+impl LockBackend for OfdBackend {
+	#[inline]
+	fn lock(
+		&self,
+		ptr: RawFilePtr,
+		mode: FlockMode,
+		is_wait: bool,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		crate::sys::backend_ofd_fcntl(ptr, Some(mode), is_wait, prange)
+	}
+
+	#[inline]
+	unsafe fn unlock(
+		&self,
+		ptr: RawFilePtr,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		crate::sys::backend_ofd_fcntl(ptr, None, true, prange)
+	}
+}
+
+/// OFD `fcntl` (if supported by the kernel) or POSIX `fcntl` for all locks.
+///
+/// One mechanism is used for whole-file and range locks: `flock` and `fcntl`
+/// locks do not conflict with each other on Linux.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AutoBackend;
+
+#[cfg(target_os = "linux")]
+mod __ofd_support {
+	use super::FULL_PRANGE;
+	use crate::err::IoErrorKind;
+	use crate::mode::FlockMode;
+	use crate::sys::RawFilePtr;
+	use core::sync::atomic::AtomicU8;
+	use core::sync::atomic::Ordering;
+
+	const UNKNOWN: u8 = 0;
+	const SUPPORTED: u8 = 1;
+	const UNSUPPORTED: u8 = 2;
+
+	static OFD_SUPPORT: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+	/// Is OFD `fcntl` supported by the kernel (Linux 3.15+).
+	///
+	/// Checked once per process with `F_OFD_GETLK` of the whole file: only an
+	/// unknown command gives `EINVAL` for it. Errors of the locks themselves
+	/// (for example, a bad range) do not change the answer, so `unlock` always
+	/// uses the same mechanism as `lock`.
+	pub fn is_supported(ptr: RawFilePtr) -> bool {
+		match OFD_SUPPORT.load(Ordering::Relaxed) {
+			SUPPORTED => return true,
+			UNSUPPORTED => return false,
+			_ => {}
+		}
+
+		let value = match crate::sys::backend_ofd_getlk(ptr, FlockMode::Shared, FULL_PRANGE) {
+			Err(e) if e.kind() == IoErrorKind::InvalidInput => UNSUPPORTED,
+			_ => SUPPORTED,
+		};
+		match OFD_SUPPORT.compare_exchange(UNKNOWN, value, Ordering::Relaxed, Ordering::Relaxed) {
+			Ok(..) => value == SUPPORTED,
+			Err(current) => current == SUPPORTED,
+		}
+	}
+}
+
+impl LockBackend for AutoBackend {
+	fn lock(
+		&self,
+		ptr: RawFilePtr,
+		mode: FlockMode,
+		is_wait: bool,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		#[cfg(target_os = "linux")]
+		if __ofd_support::is_supported(ptr) {
+			return OfdBackend.lock(ptr, mode, is_wait, prange);
+		}
+
+		PosixBackend.lock(ptr, mode, is_wait, prange)
+	}
+
+	unsafe fn unlock(
+		&self,
+		ptr: RawFilePtr,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		#[cfg(target_os = "linux")]
+		if __ofd_support::is_supported(ptr) {
+			return OfdBackend.unlock(ptr, prange);
+		}
+
+		PosixBackend.unlock(ptr, prange)
+	}
+}
+
+/// Lock backend selected by value (for example from configuration).
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LockBackendKind {
+	/// FlockBackend
+	Flock,
+	/// PosixBackend
+	Posix,
+	/// OfdBackend
+	#[cfg(target_os = "linux")]
+	#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+	Ofd,
+	/// AutoBackend
+	#[default]
+	Auto,
+}
+
+impl LockBackendKind {
+	/// Name of the backend, the same as accepted by `FromStr`.
+	pub const fn as_str(&self) -> &'static str {
+		match self {
+			Self::Flock => "flock",
+			Self::Posix => "posix",
+			#[cfg(target_os = "linux")]
+			Self::Ofd => "ofd",
+			Self::Auto => "auto",
+		}
+	}
+
+	#[inline]
+	fn as_backend(&self) -> &'static dyn LockBackend {
+		match self {
+			Self::Flock => &FlockBackend,
+			Self::Posix => &PosixBackend,
+			#[cfg(target_os = "linux")]
+			Self::Ofd => &OfdBackend,
+			Self::Auto => &AutoBackend,
+		}
+	}
+}
+
+impl FromStr for LockBackendKind {
+	type Err = IoError;
+
+	/// `flock`, `posix` (or `fcntl`), `ofd` (Linux only), `auto`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"flock" => Ok(Self::Flock),
+			"posix" | "fcntl" => Ok(Self::Posix),
+			#[cfg(target_os = "linux")]
+			"ofd" => Ok(Self::Ofd),
+			"auto" => Ok(Self::Auto),
+			_ => Err(IoError::new(
+				IoErrorKind::InvalidInput,
+				"unknown lock backend, expected: flock, posix, ofd, auto",
+			)),
+		}
+	}
+}
+
+impl LockBackend for LockBackendKind {
+	#[inline]
+	fn lock(
+		&self,
+		ptr: RawFilePtr,
+		mode: FlockMode,
+		is_wait: bool,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		self.as_backend().lock(ptr, mode, is_wait, prange)
+	}
+
+	#[inline]
+	unsafe fn unlock(
+		&self,
+		ptr: RawFilePtr,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		self.as_backend().unlock(ptr, prange)
+	}
+}
+
+/// Pointer to a file whose locks are set by the lock backend.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct BackendFilePtr(RawFilePtr);
+
+impl BackendFilePtr {
+	/// Get file pointer.
+	#[inline(always)]
+	pub const fn get_file_ptr(&self) -> RawFilePtr {
+		self.0
+	}
+}
+
+impl From<BackendFilePtr> for RawFilePtr {
+	#[inline(always)]
+	fn from(ptr: BackendFilePtr) -> Self {
+		ptr.0
+	}
+}
+
+/// Data stream whose locks (SharedFlock, ExclusiveFlock, SharedFlockRange,
+/// ExclusiveFlockRange) are set by the lock backend `B`.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BackendFile<T, B = LockBackendKind> {
+	data: T,
+	backend: B,
+}
+
+impl<T, B> BackendFile<T, B> {
+	/// Use the default lock backend `B` for the data stream.
+	#[inline]
+	pub fn new(data: T) -> Self
+	where
+		B: Default,
+	{
+		Self::with_backend(data, B::default())
+	}
+
+	/// Use the lock backend for the data stream.
+	#[inline]
+	pub const fn with_backend(data: T, backend: B) -> Self {
+		Self { data, backend }
+	}
+
+	/// Get pointer to data
+	#[inline(always)]
+	pub const fn as_data(&self) -> &T {
+		&self.data
+	}
+
+	/// Get mut pointer to data
+	#[inline(always)]
+	pub fn as_mut_data(&mut self) -> &mut T {
+		&mut self.data
+	}
+
+	/// Get the lock backend.
+	#[inline(always)]
+	pub const fn as_backend(&self) -> &B {
+		&self.backend
+	}
+
+	/// Retrieve only the data.
+	#[inline(always)]
+	pub fn into_data(self) -> T {
+		self.data
+	}
+}
+
+impl<T, B> FlockElement for BackendFile<T, B>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	B: LockBackend,
+{
+	type FilePtr = BackendFilePtr;
+
+	#[inline(always)]
+	fn as_file_ptr(&self) -> Self::FilePtr {
+		BackendFilePtr(T::as_file_ptr(&self.data))
+	}
+}
+
+impl<T, B> From<T> for BackendFile<T, B>
+where
+	B: Default,
+{
+	#[inline(always)]
+	fn from(data: T) -> Self {
+		Self::new(data)
+	}
+}
+
+impl<T, B> Deref for BackendFile<T, B> {
+	type Target = T;
+
+	#[inline(always)]
+	fn deref(&self) -> &Self::Target {
+		&self.data
+	}
+}
+
+impl<T, B> DerefMut for BackendFile<T, B> {
+	#[inline(always)]
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.data
+	}
+}
+
+impl<T, B> BackendFile<T, B>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	B: LockBackend,
+{
+	#[inline]
+	fn backend_lock<R>(
+		self,
+		mode: FlockMode,
+		is_wait: bool,
+		prange: (FlockRangePNum, FlockRangePNum),
+		next: impl FnOnce(Self) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		let ptr = T::as_file_ptr(&self.data);
+
+		match self.backend.lock(ptr, mode, is_wait, prange) {
+			Ok(()) => next(self),
+			Err(e) => errf(FlockError::new(self, e)),
+		}
+	}
+
+	#[inline]
+	fn backend_lock_flock<M, R>(
+		self,
+		is_wait: bool,
+		next: impl FnOnce(FlockLock<Self, M>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		M: FlockLockMode,
+	{
+		self.backend_lock(
+			M::MODE,
+			is_wait,
+			FULL_PRANGE,
+			|data| next(unsafe { FlockLock::force_new(data) }),
+			errf,
+		)
+	}
+
+	#[inline]
+	fn backend_lock_range<M, R, NS, NE, C>(
+		self,
+		is_wait: bool,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, M>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		M: FlockLockMode,
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		match range.into().check_range_and_get() {
+			Ok(prange) => self.backend_lock(
+				M::MODE,
+				is_wait,
+				prange,
+				|data| {
+					let safe_flock = unsafe {
+						let range = FlockRange::new_ignore_range_check(prange.0, prange.1);
+
+						FlockRangeLock::from_flock(FlockLock::force_new(data), range)
+					};
+
+					next(safe_flock)
+				},
+				errf,
+			),
+			Err(err_range) => errf(FlockError::new(self, err_range.make_io_error())),
+		}
+	}
+
+	#[inline]
+	unsafe fn backend_unlock(
+		&self,
+		prange: (FlockRangePNum, FlockRangePNum),
+	) -> Result<(), IoError> {
+		self.backend.unlock(T::as_file_ptr(&self.data), prange)
+	}
+}
+
+impl<T, B> WaitFlockUnlock for BackendFile<T, B>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	B: LockBackend,
+{
+	#[inline]
+	unsafe fn unlock_no_result(&mut self) {
+		let _e = self.backend_unlock(FULL_PRANGE);
+	}
+
+	#[inline]
+	unsafe fn unlock(&mut self) -> Result<(), IoError> {
+		self.backend_unlock(FULL_PRANGE)
+	}
+
+	#[inline]
+	unsafe fn unlock_fn<R>(
+		&mut self,
+		next: impl FnOnce() -> R,
+		errf: impl FnOnce(IoError) -> R,
+	) -> R {
+		match self.backend_unlock(FULL_PRANGE) {
+			Ok(()) => next(),
+			Err(e) => errf(e),
+		}
+	}
+}
+
+impl<T, B> WaitFlockUnlockRange for BackendFile<T, B>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	B: LockBackend,
+{
+	#[inline]
+	unsafe fn unlock_range_no_result<NS, NE, C>(&mut self, range: impl Into<FlockRange<NS, NE, C>>)
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		WaitFlockUnlockRange::unlock_range_fn(self, range, || (), |_| ())
+	}
+
+	#[inline]
+	unsafe fn unlock_range<NS, NE, C>(
+		&mut self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<(), IoError>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		WaitFlockUnlockRange::unlock_range_fn(self, range, || Ok(()), Err)
+	}
+
+	#[inline]
+	unsafe fn unlock_range_fn<R, NS, NE, C>(
+		&mut self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce() -> R,
+		errf: impl FnOnce(IoError) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		let result = match range.into().check_range_and_get() {
+			Ok(prange) => self.backend_unlock(prange),
+			Err(err_range) => Err(err_range.make_io_error()),
+		};
+
+		match result {
+			Ok(()) => next(),
+			Err(e) => errf(e),
+		}
+	}
+}
+
+impl<T, B> SharedFlock for BackendFile<T, B>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	B: LockBackend,
+{
+	#[inline]
+	fn try_lock_fn<R>(
+		self,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		self.backend_lock_flock(false, next, errf)
+	}
+
+	#[inline]
+	fn wait_lock_fn<R>(
+		self,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		SharedFlock::wait_lock_interruptible_fn(self, RetryInterrupted, next, errf)
+	}
+
+	#[inline]
+	fn wait_lock_interruptible_fn<R>(
+		self,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			data.backend_lock_flock(true, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
+}
+
+impl<T, B> ExclusiveFlock for BackendFile<T, B>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	B: LockBackend,
+{
+	#[inline]
+	fn try_lock_fn<R>(
+		self,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		self.backend_lock_flock(false, next, errf)
+	}
+
+	#[inline]
+	fn wait_lock_fn<R>(
+		self,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		ExclusiveFlock::wait_lock_interruptible_fn(self, RetryInterrupted, next, errf)
+	}
+
+	#[inline]
+	fn wait_lock_interruptible_fn<R>(
+		self,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			data.backend_lock_flock(true, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
+}
+
+impl<T, B> SharedFlockRange for BackendFile<T, B>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	B: LockBackend,
+{
+	#[inline]
+	fn try_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		self.backend_lock_range(false, range, next, errf)
+	}
+
+	#[inline]
+	fn wait_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		SharedFlockRange::wait_lock_range_interruptible_fn(
+			self,
+			range,
+			RetryInterrupted,
+			next,
+			errf,
+		)
+	}
+
+	#[inline]
+	fn wait_lock_range_interruptible_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		let range = range.into();
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			data.backend_lock_range(true, range, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
+}
+
+impl<T, B> ExclusiveFlockRange for BackendFile<T, B>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	B: LockBackend,
+{
+	#[inline]
+	fn try_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		self.backend_lock_range(false, range, next, errf)
+	}
+
+	#[inline]
+	fn wait_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		ExclusiveFlockRange::wait_lock_range_interruptible_fn(
+			self,
+			range,
+			RetryInterrupted,
+			next,
+			errf,
+		)
+	}
+
+	#[inline]
+	fn wait_lock_range_interruptible_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		let range = range.into();
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			data.backend_lock_range(true, range, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
+}
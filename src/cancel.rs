@@ -0,0 +1,147 @@
+//! Cancellation of waiting for a lock from another thread.
+//!
+//! ```rust
+//! use cluFlock::cancel::FlockCancelToken;
+//! use cluFlock::ExclusiveFlock;
+//! use std::fs::File;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let file = File::create("./file")?;
+//! 	let token = FlockCancelToken::new();
+//!
+//! 	std::thread::scope(|scope| {
+//! 		let wait_token = token.clone();
+//! 		let waiter = scope.spawn(move || {
+//! 			match ExclusiveFlock::wait_lock_cancellable(&file, &wait_token) {
+//! 				Ok(lock) => println!("{:?}", lock),
+//! 				Err(e) if e.is_cancelled() => println!("shutdown, {:?}", e.into_data()),
+//! 				Err(e) => return Err(e.into_err()),
+//! 			}
+//! 			Ok(())
+//! 		});
+//!
+//! 		token.cancel(); // shutdown
+//! 		waiter.join().unwrap()
+//! 	})
+//! }
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::mode::FlockLockMode;
+use crate::unlock::WaitFlockUnlock;
+use crate::FlockLock;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+/// Token for cancelling `wait_lock_cancellable` from another thread,
+/// clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct FlockCancelToken(Arc<CancelState>);
+
+#[derive(Debug, Default)]
+struct CancelState {
+	is_cancelled: Mutex<bool>,
+	condvar: Condvar,
+}
+
+impl FlockCancelToken {
+	/// Create a new, not cancelled token.
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	#[inline]
+	fn lock_state(&self) -> MutexGuard<'_, bool> {
+		self.0
+			.is_cancelled
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+	}
+
+	/// Cancel all current and future waits with this token.
+	pub fn cancel(&self) {
+		*self.lock_state() = true;
+		self.0.condvar.notify_all();
+	}
+
+	/// Has the token been cancelled.
+	#[inline]
+	pub fn is_cancelled(&self) -> bool {
+		*self.lock_state()
+	}
+
+	/// Sleep no longer than `timeout`, wakes up immediately on cancellation.
+	fn pause(&self, timeout: Duration) -> Result<(), IoError> {
+		let state = self.lock_state();
+		let (state, _timeout) = self
+			.0
+			.condvar
+			.wait_timeout_while(state, timeout, |is_cancelled| !*is_cancelled)
+			.unwrap_or_else(PoisonError::into_inner);
+
+		match *state {
+			true => Err(cancelled_error()),
+			false => Ok(()),
+		}
+	}
+}
+
+/// The wait for the lock was cancelled by `FlockCancelToken`
+/// (the source of the `Interrupted` error, see `FlockError::is_cancelled`).
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FlockCancelled;
+
+impl Display for FlockCancelled {
+	#[inline]
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		write!(f, "the wait for the lock was cancelled")
+	}
+}
+
+impl std::error::Error for FlockCancelled {}
+
+#[inline]
+fn cancelled_error() -> IoError {
+	IoError::new(IoErrorKind::Interrupted, FlockCancelled)
+}
+
+/// Repeat `try_lock` until the lock is set or the token is cancelled.
+///
+/// If the lock is set at the moment of cancellation, it is released
+/// and the cancellation is returned.
+pub(crate) fn try_lock_cancellable<T, M>(
+	data: T,
+	token: &FlockCancelToken,
+	try_lock: impl Fn(T) -> Result<FlockLock<T, M>, FlockError<T>>,
+) -> Result<FlockLock<T, M>, FlockError<T>>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+{
+	if token.is_cancelled() {
+		return Err(FlockError::new(data, cancelled_error()));
+	}
+
+	let lock = crate::poll::try_lock_loop(data, try_lock, |pause| token.pause(pause))?;
+	if token.is_cancelled() {
+		let (data, result) = lock.unlock_data();
+		let err = match result {
+			Ok(()) => cancelled_error(),
+			Err(e) => e,
+		};
+
+		return Err(FlockError::new(data, err));
+	}
+
+	Ok(lock)
+}
@@ -0,0 +1,153 @@
+//! Search for the lock that prevents setting a new lock (F_GETLK, F_OFD_GETLK), Unix only.
+//!
+//! Only `fcntl` locks (POSIX, OFD) are visible, `flock` locks are not reported.
+//!
+//! ```rust
+//! use cluFlock::conflict::query_conflict;
+//! use cluFlock::mode::FlockMode;
+//! use cluFlock::ExclusiveFlockRange;
+//! use std::fs::File;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let file = File::create("./file")?;
+//!
+//! 	match ExclusiveFlockRange::try_lock_range(&file, 0usize..) {
+//! 		Ok(range_lock) => println!("{:?}", range_lock),
+//! 		Err(e) if e.is_would_block() => {
+//! 			// locked by pid 1234 (exclusive, bytes 0..)
+//! 			if let Some(conflict) = query_conflict(&file, FlockMode::Exclusive, 0usize..)? {
+//! 				println!("{}", conflict);
+//! 			}
+//! 		}
+//! 		Err(e) => return Err(e.into_err()),
+//! 	}
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::IoError;
+use crate::mode::FlockMode;
+use crate::range::checker::FlockRangePNumBehChecker;
+use crate::range::pnum::FlockRangePNumBeh;
+use crate::range::FlockRange;
+use crate::sys::FlockRangePNum;
+use crate::sys::RawFilePtr;
+use core::fmt::Display;
+use core::fmt::Formatter;
+
+/// Process identifier of the owner of a POSIX lock.
+pub type FlockPid = libc::pid_t;
+
+/// Lock that prevents setting a new lock.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FlockConflict {
+	mode: FlockMode,
+	range: (FlockRangePNum, FlockRangePNum),
+	pid: Option<FlockPid>,
+}
+
+impl FlockConflict {
+	#[inline]
+	pub(crate) const fn new(
+		mode: FlockMode,
+		range: (FlockRangePNum, FlockRangePNum),
+		pid: Option<FlockPid>,
+	) -> Self {
+		Self { mode, range, pid }
+	}
+
+	/// Type of the conflicting lock.
+	#[inline(always)]
+	pub const fn mode(&self) -> FlockMode {
+		self.mode
+	}
+
+	/// Locked bytes (A..=B), `B == MAX` if the lock extends to the end of the file.
+	#[inline(always)]
+	pub const fn range(&self) -> (FlockRangePNum, FlockRangePNum) {
+		self.range
+	}
+
+	/// Does the lock extend to the end of the file (and beyond).
+	#[inline(always)]
+	pub fn is_to_end(&self) -> bool {
+		self.range.1 == <FlockRangePNum as FlockRangePNumBeh>::MAX
+	}
+
+	/// Process that owns the lock, only for POSIX locks
+	/// (OFD locks belong to the open file description, not to the process).
+	#[inline(always)]
+	pub const fn pid(&self) -> Option<FlockPid> {
+		self.pid
+	}
+}
+
+impl Display for FlockConflict {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		match self.pid {
+			Some(pid) => write!(f, "locked by pid {}", pid)?,
+			None => write!(f, "locked by open file description")?,
+		}
+
+		let mode = match self.mode {
+			FlockMode::Exclusive => "exclusive",
+			FlockMode::Shared => "shared",
+		};
+		match self.is_to_end() {
+			true => write!(f, " ({}, bytes {}..)", mode, self.range.0),
+			false => write!(f, " ({}, bytes {}..={})", mode, self.range.0, self.range.1),
+		}
+	}
+}
+
+/// Find the lock (F_GETLK) that prevents setting the `mode` lock of the range,
+/// `None` if the lock would be granted.
+///
+/// POSIX locks of the current process are not reported (they never conflict with it).
+pub fn query_conflict<D, NS, NE, C>(
+	data: &D,
+	mode: FlockMode,
+	range: impl Into<FlockRange<NS, NE, C>>,
+) -> Result<Option<FlockConflict>, IoError>
+where
+	D: FlockElement,
+	D::FilePtr: Into<RawFilePtr>,
+	NS: FlockRangePNumBeh,
+	NE: FlockRangePNumBeh + PartialOrd<NS>,
+	C: FlockRangePNumBehChecker,
+{
+	let prange = match range.into().check_range_and_get() {
+		Ok(prange) => prange,
+		Err(err_range) => return Err(err_range.make_io_error()),
+	};
+
+	crate::sys::backend_posix_getlk(data.as_file_ptr().into(), mode, prange)
+}
+
+/// Find the lock (F_OFD_GETLK) that prevents setting the `mode` OFD lock of the range,
+/// `None` if the lock would be granted.
+///
+/// Unlike `query_conflict`, POSIX locks of the current process are also reported.
+#[cfg(target_os = "linux")]
+#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+pub fn query_ofd_conflict<D, NS, NE, C>(
+	data: &D,
+	mode: FlockMode,
+	range: impl Into<FlockRange<NS, NE, C>>,
+) -> Result<Option<FlockConflict>, IoError>
+where
+	D: FlockElement,
+	D::FilePtr: Into<RawFilePtr>,
+	NS: FlockRangePNumBeh,
+	NE: FlockRangePNumBeh + PartialOrd<NS>,
+	C: FlockRangePNumBehChecker,
+{
+	let prange = match range.into().check_range_and_get() {
+		Ok(prange) => prange,
+		Err(err_range) => return Err(err_range.make_io_error()),
+	};
+
+	crate::sys::backend_ofd_getlk(data.as_file_ptr().into(), mode, prange)
+}
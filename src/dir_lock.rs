@@ -0,0 +1,227 @@
+//! Locks of whole directories (Unix only).
+//!
+//! `flock` works on a directory opened with `O_RDONLY | O_DIRECTORY`, a sentinel
+//! file is not needed. Files are opened relative to the locked directory
+//! (`openat`), so the lock and the operations refer to the same directory even
+//! if it is renamed.
+//!
+//! ```rust
+//! use cluFlock::dir_lock::LockedDir;
+//! use std::io::Write;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let dir = std::env::temp_dir().join("cluFlock_doc_dir_lock");
+//! 	std::fs::create_dir_all(&dir)?;
+//!
+//! 	let lock = LockedDir::wait_exclusive_lock(&dir)?;
+//! 	lock.create_file("data")?.write_all(b"cache")?;
+//! 	lock.remove_file("data")?;
+//!
+//! 	drop(lock); // auto unlock
+//! 	std::fs::remove_dir(&dir)
+//! }
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::mode::Exclusive;
+use crate::mode::Shared;
+use crate::sys::RawFilePtr;
+use crate::ExclusiveFlock;
+use crate::FlockLock;
+use crate::SharedFlock;
+use std::ffi::CString;
+use std::fs::File;
+use std::fs::Metadata;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::AsFd;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::BorrowedFd;
+use std::os::unix::io::FromRawFd;
+use std::os::unix::io::OwnedFd;
+use std::path::Path;
+
+/// Lock of a directory.
+pub type DirLock<M = Exclusive> = FlockLock<LockedDir, M>;
+
+/// Directory opened for locking (`O_RDONLY | O_DIRECTORY`).
+#[derive(Debug)]
+pub struct LockedDir {
+	fd: OwnedFd,
+}
+
+impl LockedDir {
+	/// Open the directory at the path.
+	#[inline]
+	pub fn open(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		let fd = open_at(libc::AT_FDCWD, path.as_ref(), DIR_FLAGS, 0)?;
+
+		Ok(Self { fd })
+	}
+
+	/// Expect to get an exclusive lock of the directory at the path.
+	#[inline]
+	pub fn wait_exclusive_lock(path: impl AsRef<Path>) -> Result<DirLock<Exclusive>, IoError> {
+		ExclusiveFlock::wait_lock(Self::open(path)?).map_err(FlockError::into_err)
+	}
+
+	/// Get an exclusive lock of the directory at the path without waiting
+	/// or get an error right away (`WouldBlock` if it is locked).
+	#[inline]
+	pub fn try_exclusive_lock(path: impl AsRef<Path>) -> Result<DirLock<Exclusive>, IoError> {
+		ExclusiveFlock::try_lock(Self::open(path)?).map_err(FlockError::into_err)
+	}
+
+	/// Expect to get an shared lock of the directory at the path.
+	#[inline]
+	pub fn wait_shared_lock(path: impl AsRef<Path>) -> Result<DirLock<Shared>, IoError> {
+		SharedFlock::wait_lock(Self::open(path)?).map_err(FlockError::into_err)
+	}
+
+	/// Get an shared lock of the directory at the path without waiting
+	/// or get an error right away (`WouldBlock` if it is locked).
+	#[inline]
+	pub fn try_shared_lock(path: impl AsRef<Path>) -> Result<DirLock<Shared>, IoError> {
+		SharedFlock::try_lock(Self::open(path)?).map_err(FlockError::into_err)
+	}
+
+	/// Open a file relative to the directory with `openat` flags
+	/// (`O_CLOEXEC` is always added).
+	#[inline]
+	pub fn open_at(
+		&self,
+		path: impl AsRef<Path>,
+		flags: libc::c_int,
+		mode: libc::mode_t,
+	) -> Result<File, IoError> {
+		let fd = open_at(self.fd.as_raw_fd(), path.as_ref(), flags, mode)?;
+
+		Ok(File::from(fd))
+	}
+
+	/// Open a file relative to the directory for reading.
+	#[inline]
+	pub fn open_file(&self, path: impl AsRef<Path>) -> Result<File, IoError> {
+		self.open_at(path, libc::O_RDONLY, 0)
+	}
+
+	/// Create (truncate) a file relative to the directory for writing.
+	#[inline]
+	pub fn create_file(&self, path: impl AsRef<Path>) -> Result<File, IoError> {
+		let flags = libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC;
+
+		self.open_at(path, flags, 0o666)
+	}
+
+	/// Open a subdirectory relative to the directory.
+	#[inline]
+	pub fn open_dir(&self, path: impl AsRef<Path>) -> Result<Self, IoError> {
+		let fd = open_at(self.fd.as_raw_fd(), path.as_ref(), DIR_FLAGS, 0)?;
+
+		Ok(Self { fd })
+	}
+
+	/// Create a subdirectory relative to the directory.
+	pub fn create_dir(&self, path: impl AsRef<Path>) -> Result<(), IoError> {
+		let path = c_path(path.as_ref())?;
+
+		match unsafe { libc::mkdirat(self.fd.as_raw_fd(), path.as_ptr(), 0o777) } {
+			0 => Ok(()),
+			_ => Err(IoError::last_os_error()),
+		}
+	}
+
+	/// Remove a file relative to the directory.
+	#[inline]
+	pub fn remove_file(&self, path: impl AsRef<Path>) -> Result<(), IoError> {
+		unlink_at(self.fd.as_raw_fd(), path.as_ref(), 0)
+	}
+
+	/// Remove an empty subdirectory relative to the directory.
+	#[inline]
+	pub fn remove_dir(&self, path: impl AsRef<Path>) -> Result<(), IoError> {
+		unlink_at(self.fd.as_raw_fd(), path.as_ref(), libc::AT_REMOVEDIR)
+	}
+
+	/// Metadata of the directory.
+	#[inline]
+	pub fn metadata(&self) -> Result<Metadata, IoError> {
+		File::from(self.fd.try_clone()?).metadata()
+	}
+
+	/// Sync the directory entries to disk (after creating, renaming or removing files).
+	#[inline]
+	pub fn sync_all(&self) -> Result<(), IoError> {
+		match unsafe { libc::fsync(self.fd.as_raw_fd()) } {
+			0 => Ok(()),
+			_ => Err(IoError::last_os_error()),
+		}
+	}
+}
+
+impl FlockElement for LockedDir {
+	type FilePtr = RawFilePtr;
+
+	#[inline(always)]
+	fn as_file_ptr(&self) -> Self::FilePtr {
+		self.fd.as_raw_fd()
+	}
+}
+
+impl AsFd for LockedDir {
+	#[inline(always)]
+	fn as_fd(&self) -> BorrowedFd<'_> {
+		self.fd.as_fd()
+	}
+}
+
+impl AsRawFd for LockedDir {
+	#[inline(always)]
+	fn as_raw_fd(&self) -> RawFilePtr {
+		self.fd.as_raw_fd()
+	}
+}
+
+impl From<LockedDir> for OwnedFd {
+	#[inline(always)]
+	fn from(dir: LockedDir) -> Self {
+		dir.fd
+	}
+}
+
+const DIR_FLAGS: libc::c_int = libc::O_RDONLY | libc::O_DIRECTORY;
+
+fn c_path(path: &Path) -> Result<CString, IoError> {
+	CString::new(path.as_os_str().as_bytes()).map_err(|_| {
+		IoError::new(
+			IoErrorKind::InvalidInput,
+			"the path contains an interior nul byte",
+		)
+	})
+}
+
+fn open_at(
+	dirfd: RawFilePtr,
+	path: &Path,
+	flags: libc::c_int,
+	mode: libc::mode_t,
+) -> Result<OwnedFd, IoError> {
+	let path = c_path(path)?;
+	let flags = flags | libc::O_CLOEXEC;
+
+	match unsafe { libc::openat(dirfd, path.as_ptr(), flags, mode as libc::c_uint) } {
+		-1 => Err(IoError::last_os_error()),
+		fd => Ok(unsafe { OwnedFd::from_raw_fd(fd) }),
+	}
+}
+
+fn unlink_at(dirfd: RawFilePtr, path: &Path, flags: libc::c_int) -> Result<(), IoError> {
+	let path = c_path(path)?;
+
+	match unsafe { libc::unlinkat(dirfd, path.as_ptr(), flags) } {
+		0 => Ok(()),
+		_ => Err(IoError::last_os_error()),
+	}
+}
@@ -56,3 +56,22 @@ where
 		T::as_file_ptr(self)
 	}
 }
+
+crate::cfg_std! {
+	if #std {
+		use std::io::BufReader;
+
+		/// Buffered reading under the lock (`FlockLock<BufReader<File>>` is `BufRead`).
+		impl<T> FlockElement for BufReader<T>
+		where
+			T: FlockElement,
+		{
+			type FilePtr = T::FilePtr;
+
+			#[inline(always)]
+			fn as_file_ptr(&self) -> Self::FilePtr {
+				T::as_file_ptr(self.get_ref())
+			}
+		}
+	}
+}
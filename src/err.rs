@@ -2,7 +2,10 @@
 //!
 
 use crate::element::FlockElement;
+use crate::mode::FlockLockMode;
 use crate::r#macro::cfg_std;
+use crate::unlock::WaitFlockUnlock;
+use crate::FlockLock;
 use core::fmt::Debug;
 use core::fmt::Display;
 use core::ops::Deref;
@@ -149,6 +152,43 @@ where
 		self.is_would_block()
 	}
 
+	/// The wait was interrupted by a signal (see `interrupt`) or cancelled (`is_cancelled`).
+	#[inline(always)]
+	pub fn is_interrupted(&self) -> bool {
+		self.err.kind() == IoErrorKind::Interrupted
+	}
+
+	/// The lock was not set before the deadline (`wait_lock_timeout`, `wait_lock_deadline`).
+	#[inline(always)]
+	pub fn is_timed_out(&self) -> bool {
+		self.err.kind() == IoErrorKind::TimedOut
+	}
+
+	cfg_std! {
+		if #std {
+			/// The wait was cancelled by `FlockCancelToken` (`wait_lock_cancellable`).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			pub fn is_cancelled(&self) -> bool {
+				self.err.kind() == IoErrorKind::Interrupted
+					&& self
+						.err
+						.get_ref()
+						.is_some_and(|e| e.is::<crate::cancel::FlockCancelled>())
+			}
+
+			/// Statistics of the attempts if they were stopped by `RetryPolicy` (`try_lock_retry`).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			pub fn retry_stats(&self) -> Option<crate::retry::RetryStats> {
+				self.err
+					.get_ref()
+					.and_then(|e| e.downcast_ref::<crate::retry::FlockRetryError>())
+					.map(|e| e.stats())
+			}
+		}
+	}
+
 	/// Get a link to data.
 	#[inline(always)]
 	pub const fn as_data(&self) -> &T {
@@ -217,3 +257,148 @@ where
 		self.as_mut_err()
 	}
 }
+
+/// Error of changing the mode of an existing lock (upgrade, downgrade),
+/// the original lock is returned with the error (or only the data, if the
+/// original lock could not be restored).
+pub struct FlockConvertError<T, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+{
+	/// `Err`, the original lock is lost.
+	lock: Result<FlockLock<T, M>, T>,
+	err: IoError,
+}
+
+cfg_std! {
+	if #std {
+		#[cfg_attr(docsrs, doc(
+			cfg(feature = "std")
+		))]
+		impl<T, M> Error for FlockConvertError<T, M> where T: FlockElement + WaitFlockUnlock, M: FlockLockMode {
+			#[inline(always)]
+			fn source(&self) -> Option<&(dyn Error + 'static)> {
+				Error::source(&self.err)
+			}
+		}
+	}
+}
+
+impl<T, M> Display for FlockConvertError<T, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+{
+	#[inline(always)]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		Display::fmt(&self.err, f)
+	}
+}
+
+impl<T, M> Debug for FlockConvertError<T, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+{
+	#[inline(always)]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		Debug::fmt(&self.err, f)
+	}
+}
+
+impl<T, M> FlockConvertError<T, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+{
+	/// Creating an error consisting of the original lock and the error itself.
+	#[inline]
+	pub const fn new(lock: FlockLock<T, M>, err: IoError) -> Self {
+		Self {
+			lock: Ok(lock),
+			err,
+		}
+	}
+
+	/// Creating an error of a conversion that has lost the original lock
+	/// (the restoration failed), only the data is returned.
+	#[inline]
+	pub const fn lost(data: T, err: IoError) -> Self {
+		Self {
+			lock: Err(data),
+			err,
+		}
+	}
+
+	/// The original lock could not be restored, the data is not locked.
+	#[inline(always)]
+	pub const fn is_lost(&self) -> bool {
+		self.lock.is_err()
+	}
+
+	/// The operation must be blocked to complete,
+	/// but it was requested that the blocking operation not be performed.
+	#[inline(always)]
+	pub fn is_would_block(&self) -> bool {
+		self.err.kind() == IoErrorKind::WouldBlock
+	}
+
+	/// Get a link to the original lock (`None` if it is lost).
+	#[inline(always)]
+	pub const fn as_lock(&self) -> Option<&FlockLock<T, M>> {
+		match &self.lock {
+			Ok(a) => Some(a),
+			Err(..) => None,
+		}
+	}
+
+	/// Get a link to err.
+	#[inline(always)]
+	pub const fn as_err(&self) -> &IoError {
+		&self.err
+	}
+
+	/// Retrieve only the original lock from the error structure
+	/// (`Err` with the unlocked data if it is lost).
+	#[inline(always)]
+	pub fn into_lock(self) -> Result<FlockLock<T, M>, T> {
+		self.lock
+	}
+
+	/// Get all data from the error structure.
+	#[inline(always)]
+	pub fn into_all(self) -> (Result<FlockLock<T, M>, T>, IoError) {
+		(self.lock, self.err)
+	}
+
+	/// Get only the error from the error structure (the original lock is released).
+	#[inline(always)]
+	pub fn into_err(self) -> IoError {
+		self.err
+	}
+}
+
+impl<T, M> From<FlockConvertError<T, M>> for IoError
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+{
+	#[inline(always)]
+	fn from(a: FlockConvertError<T, M>) -> IoError {
+		a.into_err()
+	}
+}
+
+impl<T, M> Deref for FlockConvertError<T, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+{
+	type Target = IoError;
+
+	#[inline(always)]
+	fn deref(&self) -> &Self::Target {
+		self.as_err()
+	}
+}
@@ -66,5 +66,11 @@ pub enum ErrorKind {
 	
 	/// A parameter was incorrect.
 	InvalidInput = 6,
+	
+	/// This operation was interrupted.
+	Interrupted = 7,
+	
+	/// The I/O operation's timeout expired, causing it to be canceled.
+	TimedOut = 8,
 }
 
@@ -0,0 +1,219 @@
+//! Executor-independent waiting for a lock in async code.
+//!
+//! `FlockFuture` requests the lock without waiting (`LOCK_NB`) and sleeps between
+//! attempts with the timer of your runtime (`FlockTimer`), the pauses are set
+//! by `FlockBackoff`. No thread is blocked, and dropping the future never
+//! leaves a lock.
+//!
+//! ```rust
+//! use cluFlock::future::FlockFuture;
+//! use std::fs::File;
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), std::io::Error> {
+//! 	let file = File::create("./file")?;
+//!
+//! 	// smol: `|pause| async move { smol::Timer::after(pause).await; }`
+//! 	let timer = tokio::time::sleep;
+//! 	let lock = FlockFuture::exclusive(&file, timer, Duration::from_millis(10)).await?;
+//! 	println!("{:?}", lock);
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::FlockError;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::Shared;
+use crate::unlock::WaitFlockUnlock;
+use crate::ExclusiveFlock;
+use crate::FlockLock;
+use crate::SharedFlock;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::Context;
+use core::task::Poll;
+use std::boxed::Box;
+use std::time::Duration;
+
+/// Timer of the async runtime used for pauses between attempts.
+///
+/// Implemented for `FnMut(Duration) -> impl Future<Output = ()>`
+/// (`tokio::time::sleep`, `|pause| smol::Timer::after(pause)`, ...).
+pub trait FlockTimer {
+	/// Future that completes after the pause.
+	type Sleep: Future;
+
+	/// Sleep for `pause`.
+	fn sleep(&mut self, pause: Duration) -> Self::Sleep;
+}
+
+impl<F, S> FlockTimer for F
+where
+	F: FnMut(Duration) -> S,
+	S: Future,
+{
+	type Sleep = S;
+
+	#[inline(always)]
+	fn sleep(&mut self, pause: Duration) -> Self::Sleep {
+		(self)(pause)
+	}
+}
+
+/// Pauses between attempts to get a lock.
+///
+/// Implemented for `Duration` (the same pause, endlessly)
+/// and `FnMut() -> Option<Duration>`.
+pub trait FlockBackoff {
+	/// Pause before the next attempt, `None` stops the attempts
+	/// (the last `WouldBlock` error is returned).
+	fn next_pause(&mut self) -> Option<Duration>;
+}
+
+impl FlockBackoff for Duration {
+	#[inline(always)]
+	fn next_pause(&mut self) -> Option<Duration> {
+		Some(*self)
+	}
+}
+
+impl<F> FlockBackoff for F
+where
+	F: FnMut() -> Option<Duration>,
+{
+	#[inline(always)]
+	fn next_pause(&mut self) -> Option<Duration> {
+		(self)()
+	}
+}
+
+/// Non-blocking attempt to get a lock (`ExclusiveFlock::try_lock`, `SharedFlock::try_lock`).
+type TryLockFn<T, M> = fn(T) -> Result<FlockLock<T, M>, FlockError<T>>;
+
+/// Future of the lock, the result is the same as of `try_lock`/`wait_lock`
+/// (the data is returned in the error).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct FlockFuture<T, M, Tm, B>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+	Tm: FlockTimer,
+{
+	data: Option<T>,
+	try_lock: TryLockFn<T, M>,
+	timer: Tm,
+	backoff: B,
+	sleep: Option<Pin<Box<Tm::Sleep>>>,
+}
+
+impl<T, Tm, B> FlockFuture<T, Exclusive, Tm, B>
+where
+	T: ExclusiveFlock,
+	Tm: FlockTimer,
+	B: FlockBackoff,
+{
+	/// Get an exclusive lock without blocking the thread.
+	#[inline]
+	pub fn exclusive(data: T, timer: Tm, backoff: B) -> Self {
+		Self::new(data, ExclusiveFlock::try_lock, timer, backoff)
+	}
+}
+
+impl<T, Tm, B> FlockFuture<T, Shared, Tm, B>
+where
+	T: SharedFlock,
+	Tm: FlockTimer,
+	B: FlockBackoff,
+{
+	/// Get an shared lock without blocking the thread.
+	#[inline]
+	pub fn shared(data: T, timer: Tm, backoff: B) -> Self {
+		Self::new(data, SharedFlock::try_lock, timer, backoff)
+	}
+}
+
+impl<T, M, Tm, B> FlockFuture<T, M, Tm, B>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+	Tm: FlockTimer,
+	B: FlockBackoff,
+{
+	#[inline]
+	const fn new(data: T, try_lock: TryLockFn<T, M>, timer: Tm, backoff: B) -> Self {
+		Self {
+			data: Some(data),
+			try_lock,
+			timer,
+			backoff,
+			sleep: None,
+		}
+	}
+
+	/// Get a link to data (`None` if the future has completed).
+	#[inline(always)]
+	pub const fn as_data(&self) -> Option<&T> {
+		self.data.as_ref()
+	}
+}
+
+impl<T, M, Tm, B> Debug for FlockFuture<T, M, Tm, B>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+	Tm: FlockTimer,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("FlockFuture")
+			.field("data", &self.data)
+			.field("mode", &M::MODE)
+			.field("is_sleep", &self.sleep.is_some())
+			.finish()
+	}
+}
+
+impl<T, M, Tm, B> Future for FlockFuture<T, M, Tm, B>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+	Tm: FlockTimer,
+	B: FlockBackoff,
+{
+	type Output = Result<FlockLock<T, M>, FlockError<T>>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		// Fields are never pinned (the sleep is boxed).
+		let this = unsafe { self.get_unchecked_mut() };
+
+		loop {
+			if let Some(sleep) = this.sleep.as_mut() {
+				match sleep.as_mut().poll(cx) {
+					Poll::Ready(_) => this.sleep = None,
+					Poll::Pending => return Poll::Pending,
+				}
+			}
+
+			let data = this
+				.data
+				.take()
+				.expect("`FlockFuture` polled after completion");
+			match (this.try_lock)(data) {
+				Ok(lock) => return Poll::Ready(Ok(lock)),
+				Err(e) if e.is_would_block() => match this.backoff.next_pause() {
+					Some(pause) => {
+						this.data = Some(e.into_data());
+						this.sleep = Some(Box::pin(this.timer.sleep(pause)));
+					}
+					None => return Poll::Ready(Err(e)),
+				},
+				Err(e) => return Poll::Ready(Err(e)),
+			}
+		}
+	}
+}
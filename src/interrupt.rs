@@ -0,0 +1,110 @@
+//! What to do when waiting for a lock is interrupted by a signal (`EINTR`).
+//!
+//! `wait_lock` retries the interrupted wait transparently (`RetryInterrupted`),
+//! `wait_lock_interruptible` lets you choose another policy.
+//!
+//! ```rust
+//! use cluFlock::interrupt::FlockInterruptAction;
+//! use cluFlock::interrupt::ReturnInterrupted;
+//! use cluFlock::ExclusiveFlock;
+//! use std::fs::File;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let file = File::create("./file")?;
+//!
+//! 	// return `Interrupted` on the first signal
+//! 	match ExclusiveFlock::wait_lock_interruptible(&file, ReturnInterrupted) {
+//! 		Ok(lock) => println!("{:?}", lock),
+//! 		Err(e) if e.is_interrupted() => println!("interrupted, {:?}", e.into_data()),
+//! 		Err(e) => return Err(e.into_err()),
+//! 	}
+//!
+//! 	// the hook decides
+//! 	let mut interrupts = 0;
+//! 	let lock = ExclusiveFlock::wait_lock_interruptible(&file, || {
+//! 		interrupts += 1;
+//! 		match interrupts < 10 {
+//! 			true => FlockInterruptAction::Retry,
+//! 			false => FlockInterruptAction::Return,
+//! 		}
+//! 	})?;
+//! 	println!("{:?}", lock);
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::FlockError;
+
+/// Decision of the policy about the interrupted wait.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FlockInterruptAction {
+	/// Wait for the lock again.
+	Retry,
+	/// Return the `Interrupted` error (the data is returned in the error).
+	Return,
+}
+
+/// Policy that decides what to do when waiting for a lock
+/// is interrupted by a signal (`EINTR`).
+///
+/// Implemented for `FnMut() -> FlockInterruptAction` (user hook).
+pub trait FlockInterruptPolicy {
+	/// The wait was interrupted by a signal.
+	fn on_interrupt(&mut self) -> FlockInterruptAction;
+}
+
+/// Always wait again (default for `wait_lock`).
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RetryInterrupted;
+
+impl FlockInterruptPolicy for RetryInterrupted {
+	#[inline(always)]
+	fn on_interrupt(&mut self) -> FlockInterruptAction {
+		FlockInterruptAction::Retry
+	}
+}
+
+/// Always return the `Interrupted` error.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ReturnInterrupted;
+
+impl FlockInterruptPolicy for ReturnInterrupted {
+	#[inline(always)]
+	fn on_interrupt(&mut self) -> FlockInterruptAction {
+		FlockInterruptAction::Return
+	}
+}
+
+impl<F> FlockInterruptPolicy for F
+where
+	F: FnMut() -> FlockInterruptAction,
+{
+	#[inline(always)]
+	fn on_interrupt(&mut self) -> FlockInterruptAction {
+		(self)()
+	}
+}
+
+/// Repeat the blocking `lock` while it is interrupted by a signal
+/// and `policy` allows it.
+#[inline]
+pub(crate) fn retry_interrupted<T, L>(
+	mut data: T,
+	mut policy: impl FlockInterruptPolicy,
+	lock: impl Fn(T) -> Result<L, FlockError<T>>,
+) -> Result<L, FlockError<T>>
+where
+	T: FlockElement,
+{
+	loop {
+		match lock(data) {
+			Err(e) if e.is_interrupted() => match policy.on_interrupt() {
+				FlockInterruptAction::Retry => data = e.into_data(),
+				FlockInterruptAction::Return => return Err(e),
+			},
+			result => return result,
+		}
+	}
+}
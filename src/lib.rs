@@ -103,12 +103,53 @@ fn main() -> Result<(), io::Error> {
 }
 ```
 
+5. Exclusive range lock (&File)
+
+```rust
+use cluFlock::ExclusiveFlockRange;
+use std::fs::File;
+use std::io;
+
+fn main() -> Result<(), io::Error> {
+	let file = File::create("./file")?;
+
+	// scheme: (0..=9)
+	let range_lock = ExclusiveFlockRange::wait_lock_range(&file, (0usize, 9usize))?;
+	println!("{:?}", range_lock);
+
+	Ok( () )
+}
+```
+
 # Support of platforms:
-1. Unix, Linux: Full support: SharedFlock (Wait, Try), ExclusiveFlock (Wait, Try), Unlock (Wait, Try).
+1. Unix, Linux: Full support: SharedFlock (Wait, Try), ExclusiveFlock (Wait, Try), Unlock (Wait, Try), SharedFlockRange (Wait, Try), ExclusiveFlockRange (Wait, Try) (POSIX fcntl).
 1. Windows: Full support: SharedFlock (Wait, Try), ExclusiveFlock (Wait, Try), Unlock (Wait, !Try). Unlock Try is not implemented and is considered additional unsafe functionality.
 
 # Features of platforms:
+1. All: The lock mode is a part of the guard type: `FlockLock<T, Shared>` gives only read access (`FlockReadOnly<T>`, `Read`, `Seek`), `FlockLock<T, Exclusive>` also gives `&mut T` and `Write`.
+1. All (std): `wait_lock_timeout`/`wait_lock_deadline` wait for the lock by repeating non-blocking attempts, on expiry the error is `TimedOut` and keeps the data.
+1. All (std): `wait_lock_cancellable` waits in the same way until a `cancel::FlockCancelToken` is cancelled from another thread.
+1. All (std): `future::FlockFuture` waits for the lock in any async runtime by repeating non-blocking attempts with the runtime timer.
+1. All (std): `try_lock_retry` repeats non-blocking attempts according to `retry::RetryPolicy` (fixed, exponential, decorrelated jitter).
+1. Unix, Linux: A wait interrupted by a signal (EINTR) is repeated, `wait_lock_interruptible` accepts another policy (`interrupt`).
+1. Unix, Linux (tokio): `tokio_flock::TokioFlock` waits for the lock with non-blocking attempts and the tokio timer, the runtime workers are not blocked.
+1. Unix, Linux: `path_lock::PathLock` locks a file by path, checks that the locked file is still at the path and can remove it safely.
+1. Unix, Linux: `dir_lock::LockedDir` locks whole directories (without a sentinel file) and opens files relative to the locked directory (`openat`).
+1. Unix, Linux: `pid_file::PidFile` writes the pid to a locked PID file, `PidFile::read_running` tells a running process from a stale file.
+1. Unix, Linux: `single_instance::SingleInstance` keeps one instance of an application per user, the other instances send messages to it over a Unix socket.
+1. Unix, Linux: `registry::RegistryLock` gives threads of one process the same shared/exclusive semantics as processes (one flock lock per file for all holders in the process).
+1. Unix, Linux: `reentrant::ReentrantFlock` counts nested acquisitions per thread per file and unlocks at the last one.
+1. Unix, Linux: `set::FlockSet` locks several files at once in the order of (dev, inode), all or nothing, and releases them in reverse order.
+1. Unix, Linux: `lock_file::LockFileTransaction` replaces a file through a locked `path.lock` (git `index.lock` style), with commit and rollback.
+1. Unix, Linux: `append_log::LockedAppender` appends framed records to a shared log in batches under an exclusive lock, `append_log::LockedLogReader` reads complete records under a shared lock and skips torn tails.
+1. Unix, Linux: `state::LockedState` reads a small state file under a shared lock and updates it under an exclusive lock with an atomic rename, the format is given by `state::StateCodec`.
 1. Unix, Linux: The flock system call only works between processes, there are no locks inside the process.
+1. Unix, Linux: Range locks (FlockRangeLock) use POSIX fcntl locks, they belong to the process and are released when any descriptor of the file is closed in it.
+1. Unix, Linux: `FlockLock::upgrade`/`downgrade` change the mode of the lock in place, flock conversion is not atomic (see `upgrade_with`/`downgrade_with`).
+1. Linux: OFD locks (`ofd::OfdFile`) belong to the open file description like flock, but also support ranges.
+1. Unix, Linux: The lock backend (flock, POSIX fcntl, OFD, auto) can be selected at runtime for each lock (`backend::BackendFile`).
+1. Unix, Linux: `conflict::query_conflict` reports the fcntl lock (type, range, pid) that prevents locking, flock locks are not visible to it.
+1. Linux: `proc_locks::list_file_locks` lists all locks of a file (flock, POSIX, OFD) and their waiters from `/proc/locks`.
 2. Windows: System calls (LockFileEx UnlockFileEx) work between processes and within the current process. If you use Shared and Exclusive locks, you can lock yourself in the same process.
 
 # License
@@ -126,8 +167,15 @@ Licensed under the Apache License, Version 2.0
 
 use crate::element::FlockElement;
 use crate::err::FlockError;
+use crate::interrupt::FlockInterruptPolicy;
+use crate::mode::Exclusive;
+use crate::mode::Shared;
 use crate::r#macro::cfg_std;
+use crate::range::checker::FlockRangePNumBehChecker;
+use crate::range::pnum::FlockRangePNumBeh;
+use crate::range::FlockRange;
 use crate::unlock::WaitFlockUnlock;
+use crate::unlock::WaitFlockUnlockRange;
 
 #[cfg_attr(any(linux, unix, bsd), path = "./sys/flock_unix.rs")]
 #[cfg_attr(windows, path = "./sys/LockFileEx_windows.rs")]
@@ -138,12 +186,21 @@ mod lock;
 mod r#macro;
 pub mod unlock;
 pub use crate::lock::*;
+mod mapped_lock;
+pub use crate::mapped_lock::*;
 pub mod element;
 pub mod rawfile;
 
 #[cfg_attr(docsrs, doc(cfg(not(feature = "std"))))]
 cfg_std! {
-	if #std {} else {
+	if #std {
+		use std::time::Duration;
+		use std::time::Instant;
+		use crate::cancel::FlockCancelToken;
+		use crate::retry::RetryPolicy;
+
+		mod poll;
+	} else {
 		pub mod err_nostd;
 	}
 }
@@ -152,6 +209,81 @@ pub mod range;
 mod range_lock;
 pub use crate::range_lock::*;
 
+pub mod interrupt;
+pub mod mode;
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod cancel;
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod future;
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod retry;
+
+#[cfg(unix)]
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+pub mod backend;
+
+#[cfg(unix)]
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+pub mod conflict;
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+pub mod ofd;
+
+#[cfg(all(target_os = "linux", feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "std"))))]
+pub mod proc_locks;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod path_lock;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod dir_lock;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod pid_file;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod single_instance;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod registry;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod reentrant;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod set;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod lock_file;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod append_log;
+
+#[cfg(all(unix, feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "std"))))]
+pub mod state;
+
+#[cfg(all(unix, feature = "tokio"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "tokio"))))]
+pub mod tokio_flock;
+
 /// Set exclusive lock. Only one process can hold a data flow lock.
 pub trait ExclusiveFlock
 where
@@ -160,29 +292,110 @@ where
 	/// Get an exclusive lock without waiting (if there was no lock before)
 	/// or get an error right away.
 	#[inline]
-	fn try_lock(self) -> Result<FlockLock<Self>, FlockError<Self>> {
+	fn try_lock(self) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>> {
 		ExclusiveFlock::try_lock_fn(self, Ok, Err)
 	}
 
 	/// Expect to get an exclusive lock or get an error right away.
 	#[inline]
-	fn wait_lock(self) -> Result<FlockLock<Self>, FlockError<Self>> {
+	fn wait_lock(self) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>> {
 		ExclusiveFlock::wait_lock_fn(self, Ok, Err)
 	}
 
+	/// Expect to get an exclusive lock, `policy` decides what to do
+	/// if the wait is interrupted by a signal (`wait_lock` always waits again).
+	#[inline]
+	fn wait_lock_interruptible(
+		self,
+		policy: impl FlockInterruptPolicy,
+	) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>> {
+		ExclusiveFlock::wait_lock_interruptible_fn(self, policy, Ok, Err)
+	}
+
+	cfg_std! {
+		if #std {
+			/// Expect to get an exclusive lock no longer than `timeout`,
+			/// otherwise get a `TimedOut` error (the data is returned in the error).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			fn wait_lock_timeout(
+				self,
+				timeout: Duration,
+			) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>> {
+				match Instant::now().checked_add(timeout) {
+					Some(deadline) => ExclusiveFlock::wait_lock_deadline(self, deadline),
+					None => ExclusiveFlock::wait_lock(self),
+				}
+			}
+
+			/// Expect to get an exclusive lock until `deadline`,
+			/// otherwise get a `TimedOut` error (the data is returned in the error).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			fn wait_lock_deadline(
+				self,
+				deadline: Instant,
+			) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>> {
+				crate::poll::try_lock_until(self, deadline, ExclusiveFlock::try_lock)
+			}
+
+			/// Expect to get an exclusive lock until it is set or `token` is cancelled
+			/// (`FlockError::is_cancelled`, the data is returned in the error).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			fn wait_lock_cancellable(
+				self,
+				token: &FlockCancelToken,
+			) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>> {
+				crate::cancel::try_lock_cancellable(self, token, ExclusiveFlock::try_lock)
+			}
+
+			/// Repeat attempts to get an exclusive lock without waiting according to `policy`,
+			/// the final `WouldBlock` error contains the statistics (`FlockError::retry_stats`).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			fn try_lock_retry(
+				self,
+				policy: RetryPolicy,
+			) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>> {
+				crate::retry::try_lock_retry(self, policy, ExclusiveFlock::try_lock)
+			}
+		}
+	}
+
 	/// Get an exclusive lock without waiting (if there was no lock before)
 	/// or get an error right away.
 	fn try_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R;
 	/// Expect to get an exclusive lock or get an error right away.
 	fn wait_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R;
+
+	/// Expect to get an exclusive lock, `policy` decides what to do
+	/// if the wait is interrupted by a signal.
+	///
+	/// By default `wait_lock_fn` is repeated while it returns an `Interrupted`
+	/// error and the policy allows it.
+	#[inline]
+	fn wait_lock_interruptible_fn<R>(
+		self,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			ExclusiveFlock::wait_lock_fn(data, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
 }
 
 /// Set common lock, common locks can be many.
@@ -194,84 +407,417 @@ where
 	/// Get an shared lock without waiting (if there was no lock before)
 	/// or get an error right away.
 	#[inline]
-	fn try_lock(self) -> Result<FlockLock<Self>, FlockError<Self>> {
+	fn try_lock(self) -> Result<FlockLock<Self, Shared>, FlockError<Self>> {
 		SharedFlock::try_lock_fn(self, Ok, Err)
 	}
 
 	/// Expect to get an shared lock or get an error right away.
 	#[inline]
-	fn wait_lock(self) -> Result<FlockLock<Self>, FlockError<Self>> {
+	fn wait_lock(self) -> Result<FlockLock<Self, Shared>, FlockError<Self>> {
 		SharedFlock::wait_lock_fn(self, Ok, Err)
 	}
 
+	/// Expect to get an shared lock, `policy` decides what to do
+	/// if the wait is interrupted by a signal (`wait_lock` always waits again).
+	#[inline]
+	fn wait_lock_interruptible(
+		self,
+		policy: impl FlockInterruptPolicy,
+	) -> Result<FlockLock<Self, Shared>, FlockError<Self>> {
+		SharedFlock::wait_lock_interruptible_fn(self, policy, Ok, Err)
+	}
+
+	cfg_std! {
+		if #std {
+			/// Expect to get an shared lock no longer than `timeout`,
+			/// otherwise get a `TimedOut` error (the data is returned in the error).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			fn wait_lock_timeout(
+				self,
+				timeout: Duration,
+			) -> Result<FlockLock<Self, Shared>, FlockError<Self>> {
+				match Instant::now().checked_add(timeout) {
+					Some(deadline) => SharedFlock::wait_lock_deadline(self, deadline),
+					None => SharedFlock::wait_lock(self),
+				}
+			}
+
+			/// Expect to get an shared lock until `deadline`,
+			/// otherwise get a `TimedOut` error (the data is returned in the error).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			fn wait_lock_deadline(
+				self,
+				deadline: Instant,
+			) -> Result<FlockLock<Self, Shared>, FlockError<Self>> {
+				crate::poll::try_lock_until(self, deadline, SharedFlock::try_lock)
+			}
+
+			/// Expect to get an shared lock until it is set or `token` is cancelled
+			/// (`FlockError::is_cancelled`, the data is returned in the error).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			fn wait_lock_cancellable(
+				self,
+				token: &FlockCancelToken,
+			) -> Result<FlockLock<Self, Shared>, FlockError<Self>> {
+				crate::cancel::try_lock_cancellable(self, token, SharedFlock::try_lock)
+			}
+
+			/// Repeat attempts to get an shared lock without waiting according to `policy`,
+			/// the final `WouldBlock` error contains the statistics (`FlockError::retry_stats`).
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			#[inline]
+			fn try_lock_retry(
+				self,
+				policy: RetryPolicy,
+			) -> Result<FlockLock<Self, Shared>, FlockError<Self>> {
+				crate::retry::try_lock_retry(self, policy, SharedFlock::try_lock)
+			}
+		}
+	}
+
 	/// Get an shared lock without waiting (if there was no lock before)
 	/// or get an error right away.
 	fn try_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R;
 	/// Expect to get an shared lock or get an error right away.
 	fn wait_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R;
+
+	/// Expect to get an shared lock, `policy` decides what to do
+	/// if the wait is interrupted by a signal.
+	///
+	/// By default `wait_lock_fn` is repeated while it returns an `Interrupted`
+	/// error and the policy allows it.
+	#[inline]
+	fn wait_lock_interruptible_fn<R>(
+		self,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			SharedFlock::wait_lock_fn(data, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
+}
+
+/// Set exclusive lock on the range of the data stream (A..=B).
+/// Only one process can hold a lock on the intersecting range.
+pub trait ExclusiveFlockRange
+where
+	Self: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + Sized,
+{
+	/// Get an exclusive range lock without waiting (if there was no lock before)
+	/// or get an error right away.
+	#[inline]
+	fn try_lock_range<NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<FlockRangeLock<Self, Exclusive>, FlockError<Self>>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		ExclusiveFlockRange::try_lock_range_fn(self, range, Ok, Err)
+	}
+
+	/// Expect to get an exclusive range lock or get an error right away.
+	#[inline]
+	fn wait_lock_range<NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<FlockRangeLock<Self, Exclusive>, FlockError<Self>>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		ExclusiveFlockRange::wait_lock_range_fn(self, range, Ok, Err)
+	}
+
+	/// Expect to get an exclusive range lock, `policy` decides what to do
+	/// if the wait is interrupted by a signal (`wait_lock_range` always waits again).
+	#[inline]
+	fn wait_lock_range_interruptible<NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		policy: impl FlockInterruptPolicy,
+	) -> Result<FlockRangeLock<Self, Exclusive>, FlockError<Self>>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		ExclusiveFlockRange::wait_lock_range_interruptible_fn(self, range, policy, Ok, Err)
+	}
+
+	/// Get an exclusive range lock without waiting (if there was no lock before)
+	/// or get an error right away.
+	fn try_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker;
+	/// Expect to get an exclusive range lock or get an error right away.
+	fn wait_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker;
+
+	/// Expect to get an exclusive range lock, `policy` decides what to do
+	/// if the wait is interrupted by a signal.
+	///
+	/// By default `wait_lock_range_fn` is repeated while it returns an `Interrupted`
+	/// error and the policy allows it.
+	#[inline]
+	fn wait_lock_range_interruptible_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		let range = range.into();
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			ExclusiveFlockRange::wait_lock_range_fn(data, range, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
+}
+
+/// Set common lock on the range of the data stream (A..=B),
+/// common locks can be many.
+pub trait SharedFlockRange
+where
+	Self: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + Sized,
+{
+	/// Get an shared range lock without waiting (if there was no lock before)
+	/// or get an error right away.
+	#[inline]
+	fn try_lock_range<NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<FlockRangeLock<Self, Shared>, FlockError<Self>>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		SharedFlockRange::try_lock_range_fn(self, range, Ok, Err)
+	}
+
+	/// Expect to get an shared range lock or get an error right away.
+	#[inline]
+	fn wait_lock_range<NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<FlockRangeLock<Self, Shared>, FlockError<Self>>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		SharedFlockRange::wait_lock_range_fn(self, range, Ok, Err)
+	}
+
+	/// Expect to get an shared range lock, `policy` decides what to do
+	/// if the wait is interrupted by a signal (`wait_lock_range` always waits again).
+	#[inline]
+	fn wait_lock_range_interruptible<NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		policy: impl FlockInterruptPolicy,
+	) -> Result<FlockRangeLock<Self, Shared>, FlockError<Self>>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		SharedFlockRange::wait_lock_range_interruptible_fn(self, range, policy, Ok, Err)
+	}
+
+	/// Get an shared range lock without waiting (if there was no lock before)
+	/// or get an error right away.
+	fn try_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker;
+	/// Expect to get an shared range lock or get an error right away.
+	fn wait_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker;
+
+	/// Expect to get an shared range lock, `policy` decides what to do
+	/// if the wait is interrupted by a signal.
+	///
+	/// By default `wait_lock_range_fn` is repeated while it returns an `Interrupted`
+	/// error and the policy allows it.
+	#[inline]
+	fn wait_lock_range_interruptible_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		let range = range.into();
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			SharedFlockRange::wait_lock_range_fn(data, range, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
 }
 
 /// Convenient conversion of previously used values ​​to cluFlock.
 pub trait ToFlock {
 	/// Expect to get an exclusive lock or get an error right away.
-	fn wait_exclusive_lock(self) -> Result<FlockLock<Self>, FlockError<Self>>
+	fn wait_exclusive_lock(self) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>>
 	where
 		Self: ExclusiveFlock;
 	/// Expect to get an exclusive lock or get an error right away.
 	fn wait_exclusive_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R
 	where
 		Self: ExclusiveFlock;
 
+	cfg_std! {
+		if #std {
+			/// Expect to get an exclusive lock no longer than `timeout`,
+			/// otherwise get a `TimedOut` error.
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			fn wait_exclusive_lock_timeout(
+				self,
+				timeout: Duration,
+			) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>>
+			where
+				Self: ExclusiveFlock;
+			/// Expect to get an exclusive lock until `deadline`,
+			/// otherwise get a `TimedOut` error.
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			fn wait_exclusive_lock_deadline(
+				self,
+				deadline: Instant,
+			) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>>
+			where
+				Self: ExclusiveFlock;
+		}
+	}
+
 	/// Get an exclusive lock without waiting (if there was no lock before)
 	/// or get an error right away.
-	fn try_exclusive_lock(self) -> Result<FlockLock<Self>, FlockError<Self>>
+	fn try_exclusive_lock(self) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>>
 	where
 		Self: ExclusiveFlock;
 	/// Get an exclusive lock without waiting (if there was no lock before)
 	/// or get an error right away.
 	fn try_exclusive_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R
 	where
 		Self: ExclusiveFlock;
 
 	/// Expect to get an shared lock or get an error right away.
-	fn wait_shared_lock(self) -> Result<FlockLock<Self>, FlockError<Self>>
+	fn wait_shared_lock(self) -> Result<FlockLock<Self, Shared>, FlockError<Self>>
 	where
 		Self: SharedFlock;
 	/// Expect to get an shared lock or get an error right away.
 	fn wait_shared_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R
 	where
 		Self: SharedFlock;
 
+	cfg_std! {
+		if #std {
+			/// Expect to get an shared lock no longer than `timeout`,
+			/// otherwise get a `TimedOut` error.
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			fn wait_shared_lock_timeout(
+				self,
+				timeout: Duration,
+			) -> Result<FlockLock<Self, Shared>, FlockError<Self>>
+			where
+				Self: SharedFlock;
+			/// Expect to get an shared lock until `deadline`,
+			/// otherwise get a `TimedOut` error.
+			#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+			fn wait_shared_lock_deadline(
+				self,
+				deadline: Instant,
+			) -> Result<FlockLock<Self, Shared>, FlockError<Self>>
+			where
+				Self: SharedFlock;
+		}
+	}
+
 	/// Get an shared lock without waiting (if there was no lock before)
 	/// or get an error right away.
-	fn try_shared_lock(self) -> Result<FlockLock<Self>, FlockError<Self>>
+	fn try_shared_lock(self) -> Result<FlockLock<Self, Shared>, FlockError<Self>>
 	where
 		Self: SharedFlock;
 	/// Get an shared lock without waiting (if there was no lock before)
 	/// or get an error right away.
 	fn try_shared_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R
 	where
@@ -283,7 +829,7 @@ where
 	T: FlockElement,
 {
 	#[inline(always)]
-	fn wait_exclusive_lock(self) -> Result<FlockLock<Self>, FlockError<Self>>
+	fn wait_exclusive_lock(self) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>>
 	where
 		Self: ExclusiveFlock,
 	{
@@ -293,7 +839,7 @@ where
 	#[inline(always)]
 	fn wait_exclusive_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R
 	where
@@ -302,8 +848,34 @@ where
 		ExclusiveFlock::wait_lock_fn(self, next, errf)
 	}
 
+	cfg_std! {
+		if #std {
+			#[inline(always)]
+			fn wait_exclusive_lock_timeout(
+				self,
+				timeout: Duration,
+			) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>>
+			where
+				Self: ExclusiveFlock,
+			{
+				ExclusiveFlock::wait_lock_timeout(self, timeout)
+			}
+
+			#[inline(always)]
+			fn wait_exclusive_lock_deadline(
+				self,
+				deadline: Instant,
+			) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>>
+			where
+				Self: ExclusiveFlock,
+			{
+				ExclusiveFlock::wait_lock_deadline(self, deadline)
+			}
+		}
+	}
+
 	#[inline(always)]
-	fn try_exclusive_lock(self) -> Result<FlockLock<Self>, FlockError<Self>>
+	fn try_exclusive_lock(self) -> Result<FlockLock<Self, Exclusive>, FlockError<Self>>
 	where
 		Self: ExclusiveFlock,
 	{
@@ -313,7 +885,7 @@ where
 	#[inline(always)]
 	fn try_exclusive_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R
 	where
@@ -323,7 +895,7 @@ where
 	}
 
 	#[inline(always)]
-	fn wait_shared_lock(self) -> Result<FlockLock<Self>, FlockError<Self>>
+	fn wait_shared_lock(self) -> Result<FlockLock<Self, Shared>, FlockError<Self>>
 	where
 		Self: SharedFlock,
 	{
@@ -333,7 +905,7 @@ where
 	#[inline(always)]
 	fn wait_shared_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R
 	where
@@ -342,8 +914,34 @@ where
 		SharedFlock::wait_lock_fn(self, next, errf)
 	}
 
+	cfg_std! {
+		if #std {
+			#[inline(always)]
+			fn wait_shared_lock_timeout(
+				self,
+				timeout: Duration,
+			) -> Result<FlockLock<Self, Shared>, FlockError<Self>>
+			where
+				Self: SharedFlock,
+			{
+				SharedFlock::wait_lock_timeout(self, timeout)
+			}
+
+			#[inline(always)]
+			fn wait_shared_lock_deadline(
+				self,
+				deadline: Instant,
+			) -> Result<FlockLock<Self, Shared>, FlockError<Self>>
+			where
+				Self: SharedFlock,
+			{
+				SharedFlock::wait_lock_deadline(self, deadline)
+			}
+		}
+	}
+
 	#[inline(always)]
-	fn try_shared_lock(self) -> Result<FlockLock<Self>, FlockError<Self>>
+	fn try_shared_lock(self) -> Result<FlockLock<Self, Shared>, FlockError<Self>>
 	where
 		Self: SharedFlock,
 	{
@@ -353,7 +951,7 @@ where
 	#[inline(always)]
 	fn try_shared_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R
 	where
@@ -1,53 +1,194 @@
 use crate::element::FlockElement;
+use crate::err::FlockConvertError;
 use crate::err::FlockError;
 use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::FlockMode;
+use crate::mode::Shared;
+use crate::r#macro::cfg_std;
 use crate::unlock::WaitFlockUnlock;
 use crate::ExclusiveFlock;
 use crate::SharedFlock;
 use core::fmt::Debug;
 use core::hash::Hash;
+use core::marker::PhantomData;
 use core::ops::Deref;
 use core::ops::DerefMut;
 use SafeManuallyDrop::ManuallyDrop;
 
 /// Type for securely creating and securely managing 'flock' locks.
+///
+/// The lock mode `M` (`Shared`, `Exclusive`) is part of the type: a shared guard
+/// gives only read access to the data (`FlockReadOnly<T>`, `Read`, `Seek`),
+/// an exclusive guard also gives write access (`&mut T`, `Write`).
 #[derive(/*Copy, */ Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
-pub struct FlockLock<T>
+pub struct FlockLock<T, M = Exclusive>
 where
 	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
 {
 	data: ManuallyDrop<T>,
+	_mode: PhantomData<M>,
 }
 
-impl<T> Debug for FlockLock<T>
+impl<T, M> Debug for FlockLock<T, M>
 where
 	T: Debug + FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
 {
 	#[inline]
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
 		f.debug_struct("FlockLock")
 			.field("data", &self.data as &T)
+			.field("mode", &M::MODE)
 			.finish()
 	}
 }
 
-impl<T> FlockLock<T>
+impl<T, M> FlockLock<T, M>
 where
 	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
 {
 	/// Create lock surveillance structure, unsafe because it
-	/// is not known if a lock has been created before.
+	/// is not known if a lock (of mode `M`) has been created before.
 	#[inline]
 	pub const unsafe fn force_new(data: T) -> Self {
 		Self {
 			data: ManuallyDrop::new(data),
+			_mode: PhantomData,
 		}
 	}
 
+	/// Lock mode of the guard.
+	#[inline(always)]
+	pub const fn mode(&self) -> FlockMode {
+		M::MODE
+	}
+
+	/// Get pointer to data regardless of the mode, only for the implementation
+	/// of read-side traits and wrappers.
+	#[inline(always)]
+	pub(crate) fn as_data_unchecked(&self) -> &T {
+		&self.data
+	}
+
+	/// Get raw pointer to data
+	#[inline(always)]
+	pub fn as_ptr(&self) -> *const T {
+		ManuallyDrop::as_ptr(&self.data)
+	}
+
+	/// Get mut pointer to data, only for the implementation of read-side traits
+	/// and unlocking.
+	#[inline(always)]
+	pub(crate) fn as_mut_data_unchecked(&mut self) -> &mut T {
+		&mut self.data
+	}
+
+	/// Destroy the 'flock' lock, return a good result or error.
+	#[inline]
+	pub fn unlock_fn<R>(mut self, next: impl FnOnce() -> R, errf: impl FnOnce(IoError) -> R) -> R {
+		let result =
+			unsafe { WaitFlockUnlock::unlock_fn(self.as_mut_data_unchecked(), next, errf) };
+
+		// always drop
+		ManuallyDrop::drop(&mut self.data);
+		ManuallyDrop::forget(self);
+
+		result
+	}
+
+	/// Destroy the 'flock' lock, return a good result or error.
+	#[inline]
+	pub fn unlock(self) -> Result<(), IoError> {
+		self.unlock_fn(|| Ok(()), Err)
+	}
+
+	/// Ignore flock unlock.
+	#[inline]
+	pub unsafe fn ignore_unlock_no_result(mut self) {
+		// always drop
+		self.nomove_ignore_unlock_no_result();
+		ManuallyDrop::forget(self);
+	}
+
+	/// Ignore flock unlock.
+	/// ->>> (!!! Be sure to call "forget(self)" on yourself. The FlockRange destructor should no longer be executed!)
+	/// ->>> (!!! If you suddenly forget to call "forget(self)", you will still be caught by SafeManuallyDrop)
+	#[inline]
+	pub unsafe fn nomove_ignore_unlock_no_result(&mut self) {
+		// always drop
+		ManuallyDrop::drop(&mut self.data);
+	}
+
+	/// Is FlockLock a wrapper with values, or is it actually a transparent value with no false data.
+	#[inline(always)]
+	pub const fn is_repr_transparent(&self) -> bool {
+		self.data.is_repr_transparent()
+	}
+
+	/// Ignore flock unlock. Return data only.
+	#[inline]
+	pub unsafe fn ignore_unlock(mut self) -> T {
+		let data = ManuallyDrop::take(&mut self.data);
+		ManuallyDrop::forget(self);
+
+		data
+	}
+
+	/// Destroy the 'flock' lock, return a good result or error.
+	#[inline]
+	pub fn unlock_no_err_result(mut self) {
+		unsafe {
+			WaitFlockUnlock::unlock_no_result(self.as_mut_data_unchecked());
+
+			self.ignore_unlock_no_result()
+		}
+	}
+
+	/// Destroy the "flock" lock, return data and error data.
+	#[inline]
+	pub fn unlock_data(self) -> (T, Result<(), IoError>) {
+		self.unlock_data_fn(|| Ok(()), Err)
+	}
+
+	/// Destroy the "flock" lock, return data and error data.
+	#[inline]
+	pub fn unlock_data_fn<R>(
+		mut self,
+		next: impl FnOnce() -> R,
+		errf: impl FnOnce(IoError) -> R,
+	) -> (T, R) {
+		unsafe {
+			let result = WaitFlockUnlock::unlock_fn(self.as_mut_data_unchecked(), next, errf);
+			let data = self.ignore_unlock();
+
+			(data, result)
+		}
+	}
+
+	/// Remove lock "flock", return data.
+	#[inline]
+	pub fn unlock_data_no_err_result(mut self) -> T {
+		unsafe {
+			WaitFlockUnlock::unlock_no_result(self.as_mut_data_unchecked());
+
+			self.ignore_unlock()
+		}
+	}
+}
+
+impl<T> FlockLock<T, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock,
+{
 	/// Expect to get an exclusive lock or get an error right away.
 	#[inline(always)]
-	pub fn wait_exclusive_lock(data: T) -> Result<FlockLock<T>, FlockError<T>>
+	pub fn wait_exclusive_lock(data: T) -> Result<FlockLock<T, Exclusive>, FlockError<T>>
 	where
 		T: ExclusiveFlock,
 	{
@@ -57,7 +198,7 @@ where
 	/// Get an exclusive lock without waiting (if there was no lock before)
 	/// or get an error right away.
 	#[inline(always)]
-	pub fn try_exclusive_lock(data: T) -> Result<FlockLock<T>, FlockError<T>>
+	pub fn try_exclusive_lock(data: T) -> Result<FlockLock<T, Exclusive>, FlockError<T>>
 	where
 		T: ExclusiveFlock,
 	{
@@ -68,7 +209,7 @@ where
 	#[inline(always)]
 	pub fn wait_exclusive_lock_fn<R>(
 		data: T,
-		next: impl FnOnce(FlockLock<T>) -> R,
+		next: impl FnOnce(FlockLock<T, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<T>) -> R,
 	) -> R
 	where
@@ -82,7 +223,7 @@ where
 	#[inline(always)]
 	pub fn try_exclusive_lock_fn<R>(
 		data: T,
-		next: impl FnOnce(FlockLock<T>) -> R,
+		next: impl FnOnce(FlockLock<T, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<T>) -> R,
 	) -> R
 	where
@@ -91,9 +232,38 @@ where
 		ExclusiveFlock::try_lock_fn(data, next, errf)
 	}
 
+	/// Get pointer to data
+	#[inline(always)]
+	pub fn as_data(&self) -> &T {
+		&self.data
+	}
+
+	/// Get mut pointer to data
+	#[inline(always)]
+	pub fn as_mut_data(&mut self) -> &mut T {
+		&mut self.data
+	}
+
+	/// Get raw mut pointer to data
+	#[inline(always)]
+	pub fn as_mut_ptr(&mut self) -> *mut T {
+		ManuallyDrop::as_mut_ptr(&mut self.data)
+	}
+}
+
+impl<T> FlockLock<T, Shared>
+where
+	T: FlockElement + WaitFlockUnlock,
+{
+	/// Get a read-only view of the data (`&T` is not available under a shared lock).
+	#[inline(always)]
+	pub fn as_data(&self) -> &FlockReadOnly<T> {
+		FlockReadOnly::from_ref(&self.data)
+	}
+
 	/// Expect to get an shared lock or get an error right away.
 	#[inline(always)]
-	pub fn wait_shared_lock(f: T) -> Result<FlockLock<T>, FlockError<T>>
+	pub fn wait_shared_lock(f: T) -> Result<FlockLock<T, Shared>, FlockError<T>>
 	where
 		T: SharedFlock,
 	{
@@ -103,7 +273,7 @@ where
 	/// Get an shared lock without waiting (if there was no lock before)
 	/// or get an error right away.
 	#[inline(always)]
-	pub fn try_shared_lock(f: T) -> Result<FlockLock<T>, FlockError<T>>
+	pub fn try_shared_lock(f: T) -> Result<FlockLock<T, Shared>, FlockError<T>>
 	where
 		T: SharedFlock,
 	{
@@ -114,7 +284,7 @@ where
 	#[inline(always)]
 	pub fn wait_shared_lock_fn<R>(
 		data: T,
-		next: impl FnOnce(FlockLock<T>) -> R,
+		next: impl FnOnce(FlockLock<T, Shared>) -> R,
 		errf: impl FnOnce(FlockError<T>) -> R,
 	) -> R
 	where
@@ -128,7 +298,7 @@ where
 	#[inline(always)]
 	pub fn try_shared_lock_fn<R>(
 		data: T,
-		next: impl FnOnce(FlockLock<T>) -> R,
+		next: impl FnOnce(FlockLock<T, Shared>) -> R,
 		errf: impl FnOnce(FlockError<T>) -> R,
 	) -> R
 	where
@@ -136,125 +306,135 @@ where
 	{
 		SharedFlock::try_lock_fn(data, next, errf)
 	}
-	//
-
-	/// Get pointer to data
-	#[inline(always)]
-	pub fn as_data(&self) -> &T {
-		&self.data
-	}
-
-	/// Get mut pointer to data
-	#[inline(always)]
-	pub fn as_mut_data(&mut self) -> &mut T {
-		&mut self.data
-	}
-
-	/// Get raw pointer to data
-	#[inline(always)]
-	pub fn as_ptr(&self) -> *const T {
-		ManuallyDrop::as_ptr(&self.data)
-	}
-
-	/// Get raw mut pointer to data
-	#[inline(always)]
-	pub fn as_mut_ptr(&mut self) -> *mut T {
-		ManuallyDrop::as_mut_ptr(&mut self.data)
-	}
-
-	/// Destroy the 'flock' lock, return a good result or error.
-	#[inline]
-	pub fn unlock_fn<R>(mut self, next: impl FnOnce() -> R, errf: impl FnOnce(IoError) -> R) -> R {
-		let result = unsafe { WaitFlockUnlock::unlock_fn(self.as_mut_data(), next, errf) };
-
-		// always drop
-		ManuallyDrop::drop(&mut self.data);
-		ManuallyDrop::forget(self);
-
-		result
-	}
-
-	/// Destroy the 'flock' lock, return a good result or error.
-	#[inline]
-	pub fn unlock(self) -> Result<(), IoError> {
-		self.unlock_fn(|| Ok(()), Err)
-	}
+}
 
-	/// Ignore flock unlock.
+/// Changing the mode of the lock in place (shared <-> exclusive), Unix only.
+///
+/// The conversion by `flock` is not atomic: the kernel first removes the existing lock
+/// and then sets the new one, another process may get the lock between these steps.
+/// If the conversion fails, the original mode is restored (`try_upgrade` does not wait,
+/// the others wait if another process managed to take the lock) and the original guard
+/// is returned in `FlockConvertError`. If the restoration fails, the original lock
+/// is lost and only the data is returned (`FlockConvertError::is_lost`).
+///
+/// For a strictly atomic conversion use `upgrade_with`/`downgrade_with` with a secondary
+/// guard lock (for example, a separate file), which all processes must hold exclusively
+/// while setting exclusive locks on the data stream: then nobody can take
+/// an exclusive lock between the steps of the conversion.
+#[cfg(unix)]
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+impl<T> FlockLock<T, Shared>
+where
+	T: FlockElement + WaitFlockUnlock + SharedFlock + ExclusiveFlock,
+{
+	/// Convert a shared lock to an exclusive one without waiting or get an error right away
+	/// (the shared lock is also restored without waiting, it is lost if another process
+	/// has managed to take an exclusive lock).
 	#[inline]
-	pub unsafe fn ignore_unlock_no_result(mut self) {
-		// always drop
-		self.nomove_ignore_unlock_no_result();
-		ManuallyDrop::forget(self);
+	pub fn try_upgrade(self) -> Result<FlockLock<T, Exclusive>, FlockConvertError<T, Shared>> {
+		self.convert(ExclusiveFlock::try_lock, SharedFlock::try_lock)
 	}
 
-	/// Ignore flock unlock.
-	/// ->>> (!!! Be sure to call "forget(self)" on yourself. The FlockRange destructor should no longer be executed!)
-	/// ->>> (!!! If you suddenly forget to call "forget(self)", you will still be caught by SafeManuallyDrop)
+	/// Convert a shared lock to an exclusive one, waiting for other locks to be released.
 	#[inline]
-	pub unsafe fn nomove_ignore_unlock_no_result(&mut self) {
-		// always drop
-		ManuallyDrop::drop(&mut self.data);
+	pub fn upgrade(self) -> Result<FlockLock<T, Exclusive>, FlockConvertError<T, Shared>> {
+		self.convert(ExclusiveFlock::wait_lock, SharedFlock::wait_lock)
 	}
 
-	/// Is FlockLock a wrapper with values, or is it actually a transparent value with no false data.
-	#[inline(always)]
-	pub const fn is_repr_transparent(&self) -> bool {
-		self.data.is_repr_transparent()
-	}
-
-	/// Ignore flock unlock. Return data only.
+	/// Convert a shared lock to an exclusive one while holding the exclusive guard lock.
 	#[inline]
-	pub unsafe fn ignore_unlock(mut self) -> T {
-		let data = ManuallyDrop::take(&mut self.data);
-		ManuallyDrop::forget(self);
-
-		data
+	pub fn upgrade_with<G>(
+		self,
+		guard: G,
+	) -> Result<FlockLock<T, Exclusive>, FlockConvertError<T, Shared>>
+	where
+		G: ExclusiveFlock,
+	{
+		self.convert_with(guard, Self::upgrade)
 	}
+}
 
-	/// Destroy the 'flock' lock, return a good result or error.
+#[cfg(unix)]
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+impl<T> FlockLock<T, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock + SharedFlock + ExclusiveFlock,
+{
+	/// Convert an exclusive lock to a shared one.
 	#[inline]
-	pub fn unlock_no_err_result(mut self) {
-		unsafe {
-			WaitFlockUnlock::unlock_no_result(self.as_mut_data());
-
-			self.ignore_unlock_no_result()
-		}
+	pub fn downgrade(self) -> Result<FlockLock<T, Shared>, FlockConvertError<T, Exclusive>> {
+		self.convert(SharedFlock::wait_lock, ExclusiveFlock::wait_lock)
 	}
 
-	/// Destroy the "flock" lock, return data and error data.
+	/// Convert an exclusive lock to a shared one while holding the exclusive guard lock.
 	#[inline]
-	pub fn unlock_data(self) -> (T, Result<(), IoError>) {
-		self.unlock_data_fn(|| Ok(()), Err)
+	pub fn downgrade_with<G>(
+		self,
+		guard: G,
+	) -> Result<FlockLock<T, Shared>, FlockConvertError<T, Exclusive>>
+	where
+		G: ExclusiveFlock,
+	{
+		self.convert_with(guard, Self::downgrade)
 	}
+}
 
-	/// Destroy the "flock" lock, return data and error data.
-	#[inline]
-	pub fn unlock_data_fn<R>(
-		mut self,
-		next: impl FnOnce() -> R,
-		errf: impl FnOnce(IoError) -> R,
-	) -> (T, R) {
-		unsafe {
-			let result = WaitFlockUnlock::unlock_fn(self.as_mut_data(), next, errf);
-			let data = self.ignore_unlock();
-
-			(data, result)
+#[cfg(unix)]
+impl<T, M> FlockLock<T, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+{
+	fn convert<MN>(
+		self,
+		lock: impl FnOnce(T) -> Result<FlockLock<T, MN>, FlockError<T>>,
+		restore: impl Fn(T) -> Result<FlockLock<T, M>, FlockError<T>>,
+	) -> Result<FlockLock<T, MN>, FlockConvertError<T, M>>
+	where
+		MN: FlockLockMode,
+	{
+		// The lock is changed in place, the guard is created again.
+		let data = unsafe { self.ignore_unlock() };
+
+		let (mut data, err) = match lock(data) {
+			Ok(lock) => return Ok(lock),
+			Err(e) => e.into_all(),
+		};
+		loop {
+			match restore(data) {
+				Ok(lock) => return Err(FlockConvertError::new(lock, err)),
+				Err(e) if e.as_err().kind() == IoErrorKind::Interrupted => data = e.into_data(),
+				Err(e) => {
+					// The original lock is lost, only the restoration error can be returned.
+					let (data, restore_err) = e.into_all();
+
+					return Err(FlockConvertError::lost(data, restore_err));
+				}
+			}
 		}
 	}
 
-	/// Remove lock "flock", return data.
-	#[inline]
-	pub fn unlock_data_no_err_result(mut self) -> T {
-		unsafe {
-			WaitFlockUnlock::unlock_no_result(self.as_mut_data());
+	fn convert_with<G, MN>(
+		self,
+		guard: G,
+		convert: impl FnOnce(Self) -> Result<FlockLock<T, MN>, FlockConvertError<T, M>>,
+	) -> Result<FlockLock<T, MN>, FlockConvertError<T, M>>
+	where
+		G: ExclusiveFlock,
+		MN: FlockLockMode,
+	{
+		let guard_lock = match ExclusiveFlock::wait_lock(guard) {
+			Ok(a) => a,
+			Err(e) => return Err(FlockConvertError::new(self, e.into_err())),
+		};
+		let result = convert(self);
+		drop(guard_lock);
 
-			self.ignore_unlock()
-		}
+		result
 	}
 }
 
-impl<T> AsRef<T> for FlockLock<T>
+impl<T> AsRef<T> for FlockLock<T, Exclusive>
 where
 	T: FlockElement + WaitFlockUnlock,
 {
@@ -264,7 +444,7 @@ where
 	}
 }
 
-impl<T> AsMut<T> for FlockLock<T>
+impl<T> AsMut<T> for FlockLock<T, Exclusive>
 where
 	T: FlockElement + WaitFlockUnlock,
 {
@@ -274,7 +454,7 @@ where
 	}
 }
 
-impl<T> Deref for FlockLock<T>
+impl<T> Deref for FlockLock<T, Exclusive>
 where
 	T: FlockElement + WaitFlockUnlock,
 {
@@ -286,7 +466,19 @@ where
 	}
 }
 
-impl<T> DerefMut for FlockLock<T>
+impl<T> Deref for FlockLock<T, Shared>
+where
+	T: FlockElement + WaitFlockUnlock,
+{
+	type Target = FlockReadOnly<T>;
+
+	#[inline(always)]
+	fn deref(&self) -> &Self::Target {
+		self.as_data()
+	}
+}
+
+impl<T> DerefMut for FlockLock<T, Exclusive>
 where
 	T: FlockElement + WaitFlockUnlock,
 {
@@ -296,14 +488,227 @@ where
 	}
 }
 
-impl<T> Drop for FlockLock<T>
+cfg_std! {
+	if #std {
+		use std::io::BufRead;
+		use std::io::IoSliceMut;
+		use std::io::IoSlice;
+		use std::io::Read;
+		use std::io::Seek;
+		use std::io::SeekFrom;
+		use std::io::Write;
+
+		/// Read access is available under any lock.
+		impl<T, M> Read for FlockLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + Read,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+				self.as_mut_data_unchecked().read(buf)
+			}
+
+			#[inline(always)]
+			fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize, IoError> {
+				self.as_mut_data_unchecked().read_vectored(bufs)
+			}
+
+			#[inline(always)]
+			fn read_to_end(&mut self, buf: &mut std::vec::Vec<u8>) -> Result<usize, IoError> {
+				self.as_mut_data_unchecked().read_to_end(buf)
+			}
+
+			#[inline(always)]
+			fn read_to_string(&mut self, buf: &mut std::string::String) -> Result<usize, IoError> {
+				self.as_mut_data_unchecked().read_to_string(buf)
+			}
+
+			#[inline(always)]
+			fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError> {
+				self.as_mut_data_unchecked().read_exact(buf)
+			}
+		}
+
+		/// Seek is available under any lock.
+		impl<T, M> Seek for FlockLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + Seek,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn seek(&mut self, pos: SeekFrom) -> Result<u64, IoError> {
+				self.as_mut_data_unchecked().seek(pos)
+			}
+		}
+
+		/// Write access is available only under an exclusive lock.
+		impl<T> Write for FlockLock<T, Exclusive>
+		where
+			T: FlockElement + WaitFlockUnlock + Write,
+		{
+			#[inline(always)]
+			fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+				self.as_mut_data().write(buf)
+			}
+
+			#[inline(always)]
+			fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize, IoError> {
+				self.as_mut_data().write_vectored(bufs)
+			}
+
+			#[inline(always)]
+			fn flush(&mut self) -> Result<(), IoError> {
+				self.as_mut_data().flush()
+			}
+
+			#[inline(always)]
+			fn write_all(&mut self, buf: &[u8]) -> Result<(), IoError> {
+				self.as_mut_data().write_all(buf)
+			}
+		}
+
+		/// Buffered read access is available under any lock (`FlockLock<BufReader<T>>`).
+		impl<T, M> BufRead for FlockLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + BufRead,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn fill_buf(&mut self) -> Result<&[u8], IoError> {
+				self.as_mut_data_unchecked().fill_buf()
+			}
+
+			#[inline(always)]
+			fn consume(&mut self, amt: usize) {
+				self.as_mut_data_unchecked().consume(amt)
+			}
+
+			#[inline(always)]
+			fn read_until(&mut self, byte: u8, buf: &mut std::vec::Vec<u8>) -> Result<usize, IoError> {
+				self.as_mut_data_unchecked().read_until(byte, buf)
+			}
+
+			#[inline(always)]
+			fn read_line(&mut self, buf: &mut std::string::String) -> Result<usize, IoError> {
+				self.as_mut_data_unchecked().read_line(buf)
+			}
+		}
+
+		/// Read access through a shared link, if `&T` is `Read` (`&File`).
+		impl<T, M> Read for &FlockLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock,
+			for<'a> &'a T: Read,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+				self.as_data_unchecked().read(buf)
+			}
+
+			#[inline(always)]
+			fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize, IoError> {
+				self.as_data_unchecked().read_vectored(bufs)
+			}
+		}
+
+		/// Seek through a shared link, if `&T` is `Seek` (`&File`).
+		impl<T, M> Seek for &FlockLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock,
+			for<'a> &'a T: Seek,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn seek(&mut self, pos: SeekFrom) -> Result<u64, IoError> {
+				self.as_data_unchecked().seek(pos)
+			}
+		}
+
+		/// Write access through a shared link of an exclusive lock, if `&T` is `Write` (`&File`).
+		impl<T> Write for &FlockLock<T, Exclusive>
+		where
+			T: FlockElement + WaitFlockUnlock,
+			for<'a> &'a T: Write,
+		{
+			#[inline(always)]
+			fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+				self.as_data_unchecked().write(buf)
+			}
+
+			#[inline(always)]
+			fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize, IoError> {
+				self.as_data_unchecked().write_vectored(bufs)
+			}
+
+			#[inline(always)]
+			fn flush(&mut self) -> Result<(), IoError> {
+				self.as_data_unchecked().flush()
+			}
+		}
+
+		/// The descriptor of the data (`IsTerminal` and other descriptor-based APIs).
+		#[cfg(unix)]
+		impl<T, M> std::os::unix::io::AsFd for FlockLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + std::os::unix::io::AsFd,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn as_fd(&self) -> std::os::unix::io::BorrowedFd<'_> {
+				self.as_data_unchecked().as_fd()
+			}
+		}
+
+		#[cfg(unix)]
+		impl<T, M> std::os::unix::io::AsRawFd for FlockLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + std::os::unix::io::AsRawFd,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+				self.as_data_unchecked().as_raw_fd()
+			}
+		}
+
+		/// The handle of the data (`IsTerminal` and other handle-based APIs).
+		#[cfg(windows)]
+		impl<T, M> std::os::windows::io::AsHandle for FlockLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + std::os::windows::io::AsHandle,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn as_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
+				self.as_data_unchecked().as_handle()
+			}
+		}
+
+		#[cfg(windows)]
+		impl<T, M> std::os::windows::io::AsRawHandle for FlockLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + std::os::windows::io::AsRawHandle,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
+				self.as_data_unchecked().as_raw_handle()
+			}
+		}
+	}
+}
+
+impl<T, M> Drop for FlockLock<T, M>
 where
 	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
 {
 	#[inline(always)]
 	fn drop(&mut self) {
 		unsafe {
-			WaitFlockUnlock::unlock_no_result(self.as_mut_data());
+			WaitFlockUnlock::unlock_no_result(self.as_mut_data_unchecked());
 		}
 
 		// alternative self.ignore_unlock_no_result()
@@ -311,3 +716,141 @@ where
 		unsafe { self.nomove_ignore_unlock_no_result() }
 	}
 }
+
+/// Read-only view of the data of a shared lock (`Deref` of `FlockLock<T, Shared>`).
+///
+/// The view does not give `&T` implicitly, so writing through a shared link of the
+/// data (`&File: Write`) under a shared lock is a compilation error. `as_inner`
+/// gives `&T` explicitly (to read fields of the data), writing through it is not
+/// checked: the typestate catches accidental writes, it is not a sandbox.
+///
+/// ```rust,compile_fail
+/// use cluFlock::SharedFlock;
+/// use std::fs::File;
+/// use std::io::Write;
+///
+/// fn main() -> Result<(), std::io::Error> {
+/// 	let file = File::create("./file")?;
+/// 	let shared = SharedFlock::wait_lock(file)?;
+///
+/// 	(&*shared).write_all(b"data")?; // `&FlockReadOnly<File>` is not `Write`
+///
+/// 	Ok(())
+/// }
+/// ```
+#[repr(transparent)]
+pub struct FlockReadOnly<T>(T)
+where
+	T: ?Sized;
+
+impl<T> FlockReadOnly<T>
+where
+	T: ?Sized,
+{
+	#[inline(always)]
+	pub(crate) fn from_ref(data: &T) -> &Self {
+		// `repr(transparent)`
+		unsafe { &*(data as *const T as *const Self) }
+	}
+
+	/// Get `&T` explicitly, writing through it (`&File: Write`) is not prevented.
+	#[inline(always)]
+	pub fn as_inner(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T> Debug for FlockReadOnly<T>
+where
+	T: Debug + ?Sized,
+{
+	#[inline(always)]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		Debug::fmt(&self.0, f)
+	}
+}
+
+cfg_std! {
+	if #std {
+		/// Read access through the view, if `&T` is `Read` (`&File`).
+		impl<T> std::io::Read for &FlockReadOnly<T>
+		where
+			T: ?Sized,
+			for<'a> &'a T: std::io::Read,
+		{
+			#[inline(always)]
+			fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+				(&self.0).read(buf)
+			}
+
+			#[inline(always)]
+			fn read_vectored(&mut self, bufs: &mut [std::io::IoSliceMut<'_>]) -> Result<usize, IoError> {
+				(&self.0).read_vectored(bufs)
+			}
+		}
+
+		/// Seek through the view, if `&T` is `Seek` (`&File`).
+		impl<T> std::io::Seek for &FlockReadOnly<T>
+		where
+			T: ?Sized,
+			for<'a> &'a T: std::io::Seek,
+		{
+			#[inline(always)]
+			fn seek(&mut self, pos: std::io::SeekFrom) -> Result<u64, IoError> {
+				(&self.0).seek(pos)
+			}
+		}
+
+		impl FlockReadOnly<std::fs::File> {
+			/// Queries metadata about the underlying file.
+			#[inline(always)]
+			pub fn metadata(&self) -> Result<std::fs::Metadata, IoError> {
+				self.0.metadata()
+			}
+		}
+
+		#[cfg(unix)]
+		impl<T> std::os::unix::io::AsFd for FlockReadOnly<T>
+		where
+			T: std::os::unix::io::AsFd + ?Sized,
+		{
+			#[inline(always)]
+			fn as_fd(&self) -> std::os::unix::io::BorrowedFd<'_> {
+				self.0.as_fd()
+			}
+		}
+
+		#[cfg(unix)]
+		impl<T> std::os::unix::io::AsRawFd for FlockReadOnly<T>
+		where
+			T: std::os::unix::io::AsRawFd + ?Sized,
+		{
+			#[inline(always)]
+			fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+				self.0.as_raw_fd()
+			}
+		}
+
+		#[cfg(windows)]
+		impl<T> std::os::windows::io::AsHandle for FlockReadOnly<T>
+		where
+			T: std::os::windows::io::AsHandle + ?Sized,
+		{
+			#[inline(always)]
+			fn as_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
+				self.0.as_handle()
+			}
+		}
+
+		#[cfg(windows)]
+		impl<T> std::os::windows::io::AsRawHandle for FlockReadOnly<T>
+		where
+			T: std::os::windows::io::AsRawHandle + ?Sized,
+		{
+			#[inline(always)]
+			fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
+				self.0.as_raw_handle()
+			}
+		}
+	}
+}
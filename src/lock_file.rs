@@ -0,0 +1,266 @@
+//! Transactions with a lock file in the style of git `index.lock` (Unix only).
+//!
+//! `path.lock` is created exclusively (`O_CREAT | O_EXCL`) and locked, the new
+//! content is written to it. `commit` syncs it to disk and renames it over
+//! `path`, `rollback` (or drop) removes it, readers of `path` always see the
+//! old or the new content.
+//!
+//! ```rust
+//! use cluFlock::lock_file::LockFileTransaction;
+//! use std::io::Write;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let mut transaction = LockFileTransaction::begin("./test_file")?.sync_parent_dir(true);
+//! 	transaction.write_all(b"new content")?;
+//! 	transaction.commit()?;
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::ExclusiveFlock;
+use crate::FlockLock;
+use crate::SharedFlock;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use std::ffi::OsString;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::IoSlice;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Error of beginning a transaction.
+#[derive(Debug)]
+pub enum LockFileError {
+	/// The lock file exists and is locked, another transaction is in progress.
+	Held,
+	/// The lock file exists but is not locked: it was left by a process that
+	/// has ended, or the other process has created it and not locked it yet.
+	///
+	/// Advisory only: a live transaction can be reported as `Stale` between
+	/// its `O_CREAT | O_EXCL` and `LOCK_EX`, removing the lock file on this
+	/// error alone may break it.
+	Stale,
+	/// I/O error.
+	Io(IoError),
+}
+
+impl LockFileError {
+	/// Another transaction is in progress (or has left the lock file),
+	/// `Held` and `Stale` are not distinguished.
+	#[inline(always)]
+	pub const fn is_held(&self) -> bool {
+		matches!(self, Self::Held | Self::Stale)
+	}
+}
+
+impl Display for LockFileError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::Held => write!(f, "the lock file is held by another transaction"),
+			Self::Stale => write!(f, "the lock file was left by another transaction"),
+			Self::Io(e) => Display::fmt(e, f),
+		}
+	}
+}
+
+impl std::error::Error for LockFileError {
+	#[inline]
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Held | Self::Stale => None,
+			Self::Io(e) => Some(e),
+		}
+	}
+}
+
+impl From<IoError> for LockFileError {
+	#[inline(always)]
+	fn from(e: IoError) -> Self {
+		Self::Io(e)
+	}
+}
+
+impl From<LockFileError> for IoError {
+	#[inline]
+	fn from(e: LockFileError) -> Self {
+		match e {
+			LockFileError::Held | LockFileError::Stale => IoError::new(IoErrorKind::WouldBlock, e),
+			LockFileError::Io(e) => e,
+		}
+	}
+}
+
+/// Transaction replacing the file at the path, the lock file is removed
+/// if it is not committed.
+#[derive(Debug)]
+pub struct LockFileTransaction {
+	lock: FlockLock<File>,
+	path: PathBuf,
+	lock_path: PathBuf,
+	is_sync_parent_dir: bool,
+	is_finished: bool,
+}
+
+impl LockFileTransaction {
+	/// Create and lock `path.lock`.
+	pub fn begin(path: impl AsRef<Path>) -> Result<Self, LockFileError> {
+		let path = path.as_ref().to_path_buf();
+		let lock_path = {
+			let mut a = OsString::from(path.as_os_str());
+			a.push(".lock");
+
+			PathBuf::from(a)
+		};
+
+		let file = match OpenOptions::new()
+			.write(true)
+			.create_new(true)
+			.open(&lock_path)
+		{
+			Ok(file) => file,
+			Err(e) if e.kind() == IoErrorKind::AlreadyExists => {
+				return Err(held_or_stale(&lock_path));
+			}
+			Err(e) => return Err(e.into()),
+		};
+		let lock = match ExclusiveFlock::try_lock(file) {
+			Ok(lock) => lock,
+			Err(e) => {
+				// Only this transaction has created the file.
+				let _e = std::fs::remove_file(&lock_path);
+
+				return Err(e.into_err().into());
+			}
+		};
+
+		Ok(Self {
+			lock,
+			path,
+			lock_path,
+			is_sync_parent_dir: false,
+			is_finished: false,
+		})
+	}
+
+	/// Sync the parent directory after renaming, so the commit survives a crash.
+	#[inline]
+	pub fn sync_parent_dir(mut self, is_sync: bool) -> Self {
+		self.is_sync_parent_dir = is_sync;
+		self
+	}
+
+	/// Path of the file to be replaced.
+	#[inline(always)]
+	pub fn as_path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Path of the lock file.
+	#[inline(always)]
+	pub fn as_lock_path(&self) -> &Path {
+		&self.lock_path
+	}
+
+	/// Get a link to the lock of the lock file.
+	#[inline(always)]
+	pub const fn as_lock(&self) -> &FlockLock<File> {
+		&self.lock
+	}
+
+	/// Get a link to the lock file.
+	#[inline(always)]
+	pub fn as_file(&self) -> &File {
+		self.lock.as_data()
+	}
+
+	/// Sync the new content to disk and rename it over the file.
+	/// On error the lock file is removed.
+	///
+	/// An error of syncing the parent directory (`sync_parent_dir`) is returned
+	/// after the rename: the new content is already in place, but the rename
+	/// may not survive a crash.
+	pub fn commit(mut self) -> Result<(), IoError> {
+		self.is_finished = true;
+
+		let result = self.lock.flush().and_then(|_| self.lock.sync_all());
+		if let Err(e) = result.and_then(|_| std::fs::rename(&self.lock_path, &self.path)) {
+			let _e = std::fs::remove_file(&self.lock_path);
+			return Err(e);
+		}
+
+		match self.is_sync_parent_dir {
+			true => sync_parent_dir(&self.path),
+			false => Ok(()),
+		}
+	}
+
+	/// Remove the lock file, the file is not changed.
+	#[inline]
+	pub fn rollback(mut self) -> Result<(), IoError> {
+		self.is_finished = true;
+
+		std::fs::remove_file(&self.lock_path)
+	}
+}
+
+impl Write for LockFileTransaction {
+	#[inline(always)]
+	fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+		self.lock.write(buf)
+	}
+
+	#[inline(always)]
+	fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize, IoError> {
+		self.lock.write_vectored(bufs)
+	}
+
+	#[inline(always)]
+	fn flush(&mut self) -> Result<(), IoError> {
+		self.lock.flush()
+	}
+}
+
+impl Drop for LockFileTransaction {
+	#[inline]
+	fn drop(&mut self) {
+		// Still locked, the file is removed before unlocking.
+		if !self.is_finished {
+			let _e = std::fs::remove_file(&self.lock_path);
+		}
+	}
+}
+
+/// The lock file exists: is it held by another transaction.
+fn held_or_stale(lock_path: &Path) -> LockFileError {
+	let file = match File::open(lock_path) {
+		Ok(file) => file,
+		// Removed in the meantime, it was held.
+		Err(e) if e.kind() == IoErrorKind::NotFound => return LockFileError::Held,
+		Err(e) => return e.into(),
+	};
+
+	match SharedFlock::try_lock(&file)
+		.map(drop)
+		.map_err(FlockError::into_err)
+	{
+		Ok(()) => LockFileError::Stale,
+		Err(e) if e.kind() == IoErrorKind::WouldBlock => LockFileError::Held,
+		Err(e) => e.into(),
+	}
+}
+
+/// Sync the directory of the path, a rename in it survives a crash.
+pub(crate) fn sync_parent_dir(path: &Path) -> Result<(), IoError> {
+	let parent = match path.parent() {
+		Some(a) if !a.as_os_str().is_empty() => a,
+		_ => Path::new("."),
+	};
+
+	File::open(parent)?.sync_all()
+}
@@ -0,0 +1,343 @@
+//! Guards of a part of the locked data.
+//!
+//! `FlockLock::map` and `FlockLock::try_map` project the guard onto a part of
+//! the data (a field, an element), the lock is released when the projected
+//! guard is dropped. An exclusive guard gives `&mut` access, a shared one `&`.
+//!
+//! ```rust
+//! use cluFlock::ExclusiveFlock;
+//! use std::fs::File;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let boxed = Box::new(File::open("./Cargo.toml")?);
+//! 	let file = ExclusiveFlock::wait_lock(boxed)?.map(|a| &mut **a);
+//! 	println!("{:?}", file.metadata()?.len());
+//!
+//! 	Ok(())
+//! } // auto unlock
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::IoError;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::FlockMode;
+use crate::mode::Shared;
+use crate::r#macro::cfg_std;
+use crate::unlock::WaitFlockUnlock;
+use crate::FlockLock;
+use crate::FlockReadOnly;
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::ops::DerefMut;
+use core::ptr::NonNull;
+
+cfg_std! {
+	if #std {
+		use std::boxed::Box;
+	} else {
+		extern crate alloc;
+		use alloc::boxed::Box;
+	}
+}
+
+/// Guard of a part of the locked data (`FlockLock::map`), the original lock
+/// is released when it is dropped.
+///
+/// The original guard is moved to the heap, so the projection stays valid.
+///
+/// A shared guard is sent to another thread only if `U` is `Sync`:
+///
+/// ```rust,compile_fail
+/// use cluFlock::SharedFlock;
+/// use std::cell::Cell;
+/// use std::fs::File;
+///
+/// let file = File::open("./Cargo.toml").unwrap();
+/// let cell: &'static Cell<u64> = Box::leak(Box::new(Cell::new(0)));
+/// let guard = SharedFlock::wait_lock(file).unwrap().map(|_| cell);
+///
+/// fn is_send<T: Send>(_a: T) {}
+/// is_send(guard); // `Cell` is not `Sync`
+/// ```
+///
+/// The guard is invariant in `U`, a stored link cannot be replaced with
+/// a shorter-lived one through `DerefMut`:
+///
+/// ```rust,compile_fail
+/// use cluFlock::MappedFlockLock;
+/// use std::fs::File;
+///
+/// fn shrink<'a>(guard: MappedFlockLock<File, &'static str>) -> MappedFlockLock<File, &'a str> {
+/// 	guard // `&'static str` does not shrink to `&'a str`
+/// }
+/// ```
+pub struct MappedFlockLock<T, U, M = Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock,
+	U: ?Sized,
+	M: FlockLockMode,
+{
+	ptr: NonNull<U>,
+	/// `Box::into_raw`, the data does not move while `ptr` is alive.
+	lock: NonNull<FlockLock<T, M>>,
+	/// Invariant in `U` (like `&mut U`): `NonNull<U>` alone would let
+	/// `MappedFlockLock<T, &'static str>` shrink to `MappedFlockLock<T, &'a str>`.
+	_marker: PhantomData<*mut U>,
+}
+
+// Exclusive: the guard owns the only access to `U` (like `&mut U`).
+unsafe impl<T, U> Send for MappedFlockLock<T, U, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock + Send,
+	U: ?Sized + Send,
+{
+}
+
+unsafe impl<T, U> Sync for MappedFlockLock<T, U, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock + Sync,
+	U: ?Sized + Sync,
+{
+}
+
+// Shared: the projection may point outside of `T` and be shared (like `&U`).
+unsafe impl<T, U> Send for MappedFlockLock<T, U, Shared>
+where
+	T: FlockElement + WaitFlockUnlock + Send,
+	U: ?Sized + Sync,
+{
+}
+
+unsafe impl<T, U> Sync for MappedFlockLock<T, U, Shared>
+where
+	T: FlockElement + WaitFlockUnlock + Sync,
+	U: ?Sized + Sync,
+{
+}
+
+impl<T> FlockLock<T, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock,
+{
+	/// Get a guard of a part of the data, the lock stays attached to it.
+	#[inline]
+	pub fn map<U>(self, f: impl FnOnce(&mut T) -> &mut U) -> MappedFlockLock<T, U, Exclusive>
+	where
+		U: ?Sized,
+	{
+		match self.try_map(|data| Some(f(data))) {
+			Ok(a) => a,
+			Err(_) => unreachable!(),
+		}
+	}
+
+	/// Get a guard of a part of the data or get the original guard back.
+	pub fn try_map<U>(
+		self,
+		f: impl FnOnce(&mut T) -> Option<&mut U>,
+	) -> Result<MappedFlockLock<T, U, Exclusive>, Self>
+	where
+		U: ?Sized,
+	{
+		// Releases the lock if `f` panics.
+		let whole = MappedFlockLock::new(self);
+
+		match f(unsafe { &mut *whole.ptr.as_ptr() }) {
+			Some(a) => Ok(whole.with_ptr(NonNull::from(a))),
+			None => Err(whole.into_lock()),
+		}
+	}
+}
+
+impl<T> FlockLock<T, Shared>
+where
+	T: FlockElement + WaitFlockUnlock,
+{
+	/// Get a guard of a part of the data, the lock stays attached to it.
+	#[inline]
+	pub fn map<U>(self, f: impl FnOnce(&T) -> &U) -> MappedFlockLock<T, U, Shared>
+	where
+		U: ?Sized,
+	{
+		match self.try_map(|data| Some(f(data))) {
+			Ok(a) => a,
+			Err(_) => unreachable!(),
+		}
+	}
+
+	/// Get a guard of a part of the data or get the original guard back.
+	pub fn try_map<U>(
+		self,
+		f: impl FnOnce(&T) -> Option<&U>,
+	) -> Result<MappedFlockLock<T, U, Shared>, Self>
+	where
+		U: ?Sized,
+	{
+		// Releases the lock if `f` panics.
+		let whole = MappedFlockLock::new(self);
+
+		match f(unsafe { &*whole.ptr.as_ptr() }) {
+			Some(a) => Ok(whole.with_ptr(NonNull::from(a))),
+			None => Err(whole.into_lock()),
+		}
+	}
+}
+
+impl<T, U, M> MappedFlockLock<T, U, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	U: ?Sized,
+	M: FlockLockMode,
+{
+	/// Lock mode of the guard.
+	#[inline(always)]
+	pub const fn mode(&self) -> FlockMode {
+		M::MODE
+	}
+
+	/// Change the projection (the new one points into the same data).
+	#[inline]
+	fn with_ptr<N>(self, ptr: NonNull<N>) -> MappedFlockLock<T, N, M>
+	where
+		N: ?Sized,
+	{
+		let lock = self.lock;
+		core::mem::forget(self);
+
+		MappedFlockLock {
+			ptr,
+			lock,
+			_marker: PhantomData,
+		}
+	}
+
+	/// Forget the projection and get the original guard.
+	#[inline]
+	pub fn into_lock(self) -> FlockLock<T, M> {
+		let lock = unsafe { Box::from_raw(self.lock.as_ptr()) };
+		core::mem::forget(self);
+
+		*lock
+	}
+
+	/// Destroy the 'flock' lock, return a good result or error.
+	#[inline]
+	pub fn unlock(self) -> Result<(), IoError> {
+		self.into_lock().unlock()
+	}
+}
+
+impl<T, M> MappedFlockLock<T, T, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	M: FlockLockMode,
+{
+	/// Move the guard to the heap, the projection is the whole data.
+	fn new(lock: FlockLock<T, M>) -> Self {
+		let lock = unsafe { NonNull::new_unchecked(Box::into_raw(Box::new(lock))) };
+		let ptr = NonNull::from(unsafe { (*lock.as_ptr()).as_mut_data_unchecked() });
+
+		Self {
+			ptr,
+			lock,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<T, U> MappedFlockLock<T, U, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock,
+	U: ?Sized,
+{
+	/// Get a link to the part of the data.
+	#[inline(always)]
+	pub fn as_data(&self) -> &U {
+		unsafe { self.ptr.as_ref() }
+	}
+
+	/// Get a mutable link to the part of the data.
+	#[inline(always)]
+	pub fn as_mut_data(&mut self) -> &mut U {
+		unsafe { self.ptr.as_mut() }
+	}
+}
+
+impl<T, U> MappedFlockLock<T, U, Shared>
+where
+	T: FlockElement + WaitFlockUnlock,
+	U: ?Sized,
+{
+	/// Get a read-only view of the part of the data.
+	#[inline(always)]
+	pub fn as_data(&self) -> &FlockReadOnly<U> {
+		FlockReadOnly::from_ref(unsafe { self.ptr.as_ref() })
+	}
+}
+
+impl<T, U, M> Debug for MappedFlockLock<T, U, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	U: ?Sized + Debug,
+	M: FlockLockMode,
+{
+	#[inline]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		f.debug_struct("MappedFlockLock")
+			.field("data", &unsafe { self.ptr.as_ref() })
+			.field("mode", &M::MODE)
+			.finish()
+	}
+}
+
+impl<T, U> Deref for MappedFlockLock<T, U, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock,
+	U: ?Sized,
+{
+	type Target = U;
+
+	#[inline(always)]
+	fn deref(&self) -> &Self::Target {
+		self.as_data()
+	}
+}
+
+impl<T, U> Deref for MappedFlockLock<T, U, Shared>
+where
+	T: FlockElement + WaitFlockUnlock,
+	U: ?Sized,
+{
+	type Target = FlockReadOnly<U>;
+
+	#[inline(always)]
+	fn deref(&self) -> &Self::Target {
+		self.as_data()
+	}
+}
+
+impl<T, U> DerefMut for MappedFlockLock<T, U, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock,
+	U: ?Sized,
+{
+	#[inline(always)]
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.as_mut_data()
+	}
+}
+
+impl<T, U, M> Drop for MappedFlockLock<T, U, M>
+where
+	T: FlockElement + WaitFlockUnlock,
+	U: ?Sized,
+	M: FlockLockMode,
+{
+	#[inline]
+	fn drop(&mut self) {
+		// The original guard releases the lock.
+		drop(unsafe { Box::from_raw(self.lock.as_ptr()) });
+	}
+}
@@ -0,0 +1,72 @@
+//! Lock modes.
+//!
+//! The mode of FlockLock (FlockRangeLock) is a part of its type, writing under a shared lock
+//! is a compilation error:
+//!
+//! ```rust,compile_fail
+//! use cluFlock::SharedFlock;
+//! use std::fs::File;
+//! use std::io::Write;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let file = File::create("./file")?;
+//! 	let mut shared = SharedFlock::wait_lock(file)?; // FlockLock<File, Shared>
+//!
+//! 	shared.write_all(b"data")?; // `Write` is implemented only for FlockLock<T, Exclusive>
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+/// Lock mode selected at runtime.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FlockMode {
+	/// Common lock, common locks can be many.
+	Shared,
+	/// Only one process can hold the lock.
+	Exclusive,
+}
+
+impl FlockMode {
+	/// Is the lock exclusive.
+	#[inline(always)]
+	pub const fn is_exclusive(&self) -> bool {
+		matches!(self, Self::Exclusive)
+	}
+
+	/// Is the lock shared.
+	#[inline(always)]
+	pub const fn is_shared(&self) -> bool {
+		matches!(self, Self::Shared)
+	}
+}
+
+/// Lock mode known at compile time (`Shared`, `Exclusive`),
+/// the second type parameter of FlockLock and FlockRangeLock.
+pub trait FlockLockMode: __sealed::Sealed {
+	/// Lock mode selected at runtime.
+	const MODE: FlockMode;
+}
+
+mod __sealed {
+	pub trait Sealed {}
+
+	impl Sealed for super::Shared {}
+	impl Sealed for super::Exclusive {}
+}
+
+/// Shared lock: the guard gives only read access (`FlockReadOnly<T>`, `Read`, `Seek`).
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Shared;
+
+impl FlockLockMode for Shared {
+	const MODE: FlockMode = FlockMode::Shared;
+}
+
+/// Exclusive lock: the guard also gives write access (`&mut T`, `Write`).
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Exclusive;
+
+impl FlockLockMode for Exclusive {
+	const MODE: FlockMode = FlockMode::Exclusive;
+}
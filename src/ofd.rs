@@ -0,0 +1,84 @@
+//! Open File Description (OFD) locks, Linux only.
+//!
+//! OFD locks are set by `fcntl` (F_OFD_SETLK, F_OFD_SETLKW) and, like `flock`,
+//! belong to the open file description, not to the process:
+//! they are not lost when another descriptor of the file is closed and they
+//! conflict between threads that opened the file separately.
+//! Unlike `flock` they support byte ranges (FlockRangeLock).
+//!
+//! ```rust
+//! use cluFlock::ofd::OfdFile;
+//! use cluFlock::ExclusiveFlock;
+//! use std::fs::File;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let file = File::create("./file")?;
+//!
+//! 	let file_lock = ExclusiveFlock::wait_lock(OfdFile::new(&file))?;
+//! 	println!("{:?}", file_lock);
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::backend::BackendFile;
+use crate::backend::OfdBackend;
+use crate::element::FlockElement;
+use crate::err::IoError;
+use crate::mode::FlockMode;
+use crate::range::checker::FlockRangePNumBehChecker;
+use crate::range::pnum::FlockRangePNumBeh;
+use crate::range::FlockRange;
+use crate::sys::RawFilePtr;
+
+/// Data stream whose locks (SharedFlock, ExclusiveFlock, SharedFlockRange,
+/// ExclusiveFlockRange) are set by OFD `fcntl` instead of `flock`.
+pub type OfdFile<T> = BackendFile<T, OfdBackend>;
+
+impl<T> BackendFile<T, OfdBackend>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+{
+	/// Check (F_OFD_GETLK) whether an exclusive lock of the range
+	/// can be set right now, the lock itself is not set.
+	#[inline]
+	pub fn test_exclusive_lock<NS, NE, C>(
+		&self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<bool, IoError>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		self.test_lock(FlockMode::Exclusive, range)
+	}
+
+	/// Check (F_OFD_GETLK) whether a shared lock of the range
+	/// can be set right now, the lock itself is not set.
+	#[inline]
+	pub fn test_shared_lock<NS, NE, C>(
+		&self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<bool, IoError>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		self.test_lock(FlockMode::Shared, range)
+	}
+
+	fn test_lock<NS, NE, C>(
+		&self,
+		mode: FlockMode,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<bool, IoError>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		crate::conflict::query_ofd_conflict(self.as_data(), mode, range).map(|a| a.is_none())
+	}
+}
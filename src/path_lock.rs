@@ -0,0 +1,232 @@
+//! Lock files by path with protection against the unlink race (Unix only).
+//!
+//! If one process removes the lock file while another has just opened it
+//! and waits for its lock, the second one would lock a file that no longer
+//! has a path. `PathLock` checks after locking that the locked descriptor is
+//! still the file at the path (dev, inode) and opens the path again if not.
+//! With `remove_on_drop` the file is removed while it is still locked, so
+//! removal is safe for the other processes following this protocol.
+//!
+//! ```rust
+//! use cluFlock::path_lock::PathLock;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let lock = PathLock::wait_exclusive_lock("./test_file")?.remove_on_drop();
+//! 	println!("{:?}", lock);
+//!
+//! 	Ok(())
+//! } // auto remove, auto unlock
+//! ```
+
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::Shared;
+use crate::ExclusiveFlock;
+use crate::FlockLock;
+use crate::FlockReadOnly;
+use crate::SharedFlock;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::path::PathBuf;
+use SafeManuallyDrop::ManuallyDrop;
+
+/// Lock of the file at the path, the file is created if it does not exist.
+#[derive(Debug)]
+pub struct PathLock<M = Exclusive>
+where
+	M: FlockLockMode,
+{
+	lock: ManuallyDrop<FlockLock<File, M>>,
+	path: PathBuf,
+	is_remove_on_drop: bool,
+}
+
+impl PathLock<Exclusive> {
+	/// Expect to get an exclusive lock of the file at the path.
+	#[inline]
+	pub fn wait_exclusive_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), &create_options(), ExclusiveFlock::wait_lock)
+	}
+
+	/// Get an exclusive lock of the file at the path without waiting
+	/// or get an error right away (`WouldBlock` if it is locked).
+	#[inline]
+	pub fn try_exclusive_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), &create_options(), ExclusiveFlock::try_lock)
+	}
+
+	/// Remove the file when the lock is released (before unlocking),
+	/// if it is still the file at the path.
+	#[inline]
+	pub fn remove_on_drop(mut self) -> Self {
+		self.is_remove_on_drop = true;
+		self
+	}
+
+	/// Will the file be removed when the lock is released.
+	#[inline(always)]
+	pub const fn is_remove_on_drop(&self) -> bool {
+		self.is_remove_on_drop
+	}
+
+	/// Get a link to the file.
+	#[inline(always)]
+	pub fn as_file(&self) -> &File {
+		self.lock.as_data()
+	}
+}
+
+impl PathLock<Shared> {
+	/// Expect to get an shared lock of the file at the path.
+	#[inline]
+	pub fn wait_shared_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), &create_options(), SharedFlock::wait_lock)
+	}
+
+	/// Get an shared lock of the file at the path without waiting
+	/// or get an error right away (`WouldBlock` if it is locked).
+	#[inline]
+	pub fn try_shared_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), &create_options(), SharedFlock::try_lock)
+	}
+
+	/// Expect to get an shared lock of the existing file at the path, the file
+	/// is opened read-only (`NotFound` if it does not exist).
+	#[inline]
+	pub fn wait_shared_lock_existing(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(
+			path.as_ref(),
+			OpenOptions::new().read(true),
+			SharedFlock::wait_lock,
+		)
+	}
+
+	/// Get an shared lock of the existing file at the path without waiting
+	/// or get an error right away, the file is opened read-only
+	/// (`NotFound` if it does not exist, `WouldBlock` if it is locked).
+	#[inline]
+	pub fn try_shared_lock_existing(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(
+			path.as_ref(),
+			OpenOptions::new().read(true),
+			SharedFlock::try_lock,
+		)
+	}
+
+	/// Get a read-only link to the file.
+	#[inline(always)]
+	pub fn as_file(&self) -> &FlockReadOnly<File> {
+		self.lock.as_data()
+	}
+}
+
+impl<M> PathLock<M>
+where
+	M: FlockLockMode,
+{
+	/// Open the file, lock it and check that it is still at the path.
+	fn open_lock(
+		path: &Path,
+		options: &OpenOptions,
+		lock: impl Fn(File) -> Result<FlockLock<File, M>, FlockError<File>>,
+	) -> Result<Self, IoError> {
+		loop {
+			let file = options.open(path)?;
+			let lock = lock(file).map_err(FlockError::into_err)?;
+
+			let locked = lock.as_data_unchecked().metadata()?;
+			match std::fs::metadata(path) {
+				Ok(a) if a.dev() == locked.dev() && a.ino() == locked.ino() => {
+					return Ok(Self {
+						lock: ManuallyDrop::new(lock),
+						path: path.to_path_buf(),
+						is_remove_on_drop: false,
+					});
+				}
+				// The file was removed or replaced after opening, try again.
+				Ok(..) => {}
+				Err(e) if e.kind() == IoErrorKind::NotFound => {}
+				Err(e) => return Err(e),
+			}
+		}
+	}
+
+	/// Path of the file.
+	#[inline(always)]
+	pub fn as_path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Get a link to the lock.
+	#[inline(always)]
+	pub fn as_lock(&self) -> &FlockLock<File, M> {
+		&self.lock
+	}
+
+	/// Get the lock of the file, the file will not be removed.
+	#[inline]
+	pub fn into_lock(mut self) -> FlockLock<File, M> {
+		#[allow(unused_unsafe)]
+		let lock = unsafe { ManuallyDrop::take(&mut self.lock) };
+		let _path = unsafe { core::ptr::read(&self.path) };
+		ManuallyDrop::forget(self);
+
+		lock
+	}
+
+	/// Release the lock (and remove the file if requested).
+	#[inline]
+	pub fn unlock(mut self) -> Result<(), IoError> {
+		let remove_result = self.remove_if_requested();
+
+		self.into_lock().unlock()?;
+		remove_result
+	}
+
+	fn remove_if_requested(&mut self) -> Result<(), IoError> {
+		if !core::mem::take(&mut self.is_remove_on_drop) {
+			return Ok(());
+		}
+
+		// Do not remove a file that has replaced the locked one at the path.
+		let locked = self.lock.as_data_unchecked().metadata()?;
+		match std::fs::metadata(&self.path) {
+			Ok(a) if a.dev() == locked.dev() && a.ino() == locked.ino() => {}
+			Ok(..) => return Ok(()),
+			Err(e) if e.kind() == IoErrorKind::NotFound => return Ok(()),
+			Err(e) => return Err(e),
+		}
+
+		// Still locked: a process that opened the file before removal
+		// will see that it is no longer at the path.
+		std::fs::remove_file(&self.path)
+	}
+}
+
+impl<M> Drop for PathLock<M>
+where
+	M: FlockLockMode,
+{
+	#[inline]
+	fn drop(&mut self) {
+		let _e = self.remove_if_requested();
+
+		#[allow(unused_unsafe)]
+		unsafe {
+			ManuallyDrop::drop(&mut self.lock)
+		}
+	}
+}
+
+/// Options to open (create) the file for writing.
+fn create_options() -> OpenOptions {
+	let mut options = OpenOptions::new();
+	options.read(true).write(true).create(true).truncate(false);
+
+	options
+}
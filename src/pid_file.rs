@@ -0,0 +1,198 @@
+//! PID file locked for the lifetime of the process (Unix only).
+//!
+//! ```rust
+//! use cluFlock::pid_file::PidFile;
+//! use cluFlock::pid_file::PidFileStatus;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	// daemon
+//! 	let pid_file = PidFile::acquire("./test_file")?;
+//!
+//! 	// `status` command
+//! 	match PidFile::read_running("./test_file")? {
+//! 		PidFileStatus::Running(pid) => println!("running, pid {:?}", pid),
+//! 		PidFileStatus::Stale(pid) => println!("not running, stale pid {:?}", pid),
+//! 		PidFileStatus::Unknown => println!("starting or not running, no pid"),
+//! 		PidFileStatus::Absent => println!("not running"),
+//! 	}
+//!
+//! 	drop(pid_file); // auto remove, auto unlock
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::conflict::FlockPid;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::path_lock::PathLock;
+use crate::FlockLock;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::Write;
+use std::path::Path;
+
+/// PID file of the current process, locked exclusively until it is dropped
+/// (then it is removed).
+#[derive(Debug)]
+pub struct PidFile {
+	lock: PathLock,
+	pid: FlockPid,
+}
+
+/// State of the process that owns the PID file.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PidFileStatus {
+	/// The file is locked, the process is running
+	/// (`None` if the pid has not been written yet).
+	Running(Option<FlockPid>),
+	/// The file is not locked and has a pid, the process has ended
+	/// without removing it.
+	///
+	/// Advisory only: a starting process may have opened the file and not
+	/// locked it yet, removing the file on this status alone may break it.
+	Stale(FlockPid),
+	/// The file is not locked and has no pid: a starting process has created
+	/// it and not locked or written it yet, or a process has ended before
+	/// writing it. It is not safe to treat it as stale.
+	Unknown,
+	/// There is no file.
+	Absent,
+}
+
+/// Error of acquiring the PID file.
+#[derive(Debug)]
+pub enum PidFileError {
+	/// The file is locked by another process (its recorded pid,
+	/// `None` if the pid has not been written yet).
+	Running(Option<FlockPid>),
+	/// I/O error.
+	Io(IoError),
+}
+
+impl Display for PidFileError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::Running(Some(pid)) => write!(f, "the pid file is locked by pid {}", pid),
+			Self::Running(None) => write!(f, "the pid file is locked"),
+			Self::Io(e) => Display::fmt(e, f),
+		}
+	}
+}
+
+impl std::error::Error for PidFileError {
+	#[inline]
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Running(..) => None,
+			Self::Io(e) => Some(e),
+		}
+	}
+}
+
+impl From<IoError> for PidFileError {
+	#[inline(always)]
+	fn from(e: IoError) -> Self {
+		Self::Io(e)
+	}
+}
+
+impl From<PidFileError> for IoError {
+	#[inline]
+	fn from(e: PidFileError) -> Self {
+		match e {
+			PidFileError::Running(..) => IoError::new(IoErrorKind::WouldBlock, e),
+			PidFileError::Io(e) => e,
+		}
+	}
+}
+
+impl PidFile {
+	/// Lock the PID file exclusively, replace its content with the pid
+	/// of the current process and sync it to disk.
+	pub fn acquire(path: impl AsRef<Path>) -> Result<Self, PidFileError> {
+		let path = path.as_ref();
+		let lock = match PathLock::try_exclusive_lock(path) {
+			Ok(lock) => lock.remove_on_drop(),
+			Err(e) if e.kind() == IoErrorKind::WouldBlock => {
+				return Err(PidFileError::Running(read_pid(path)?));
+			}
+			Err(e) => return Err(e.into()),
+		};
+
+		let pid = std::process::id() as FlockPid;
+		let mut file = lock.as_file();
+		file.set_len(0)?;
+		file.rewind()?;
+		writeln!(file, "{}", pid)?;
+		file.sync_all()?;
+
+		Ok(Self { lock, pid })
+	}
+
+	/// Find out if the process recorded in the PID file is running
+	/// (the file is locked).
+	pub fn read_running(path: impl AsRef<Path>) -> Result<PidFileStatus, IoError> {
+		let path = path.as_ref();
+
+		// The locked file is checked to be still at the path, a removed
+		// and replaced file is not reported as stale.
+		let status = match PathLock::try_shared_lock_existing(path) {
+			Ok(lock) => match parse_pid(lock.as_file())? {
+				Some(pid) => PidFileStatus::Stale(pid),
+				None => PidFileStatus::Unknown,
+			},
+			Err(e) if e.kind() == IoErrorKind::WouldBlock => {
+				PidFileStatus::Running(read_pid(path)?)
+			}
+			Err(e) if e.kind() == IoErrorKind::NotFound => PidFileStatus::Absent,
+			Err(e) => return Err(e),
+		};
+
+		Ok(status)
+	}
+
+	/// Pid written to the file.
+	#[inline(always)]
+	pub const fn pid(&self) -> FlockPid {
+		self.pid
+	}
+
+	/// Path of the file.
+	#[inline(always)]
+	pub fn as_path(&self) -> &Path {
+		self.lock.as_path()
+	}
+
+	/// Get a link to the lock.
+	#[inline(always)]
+	pub fn as_lock(&self) -> &FlockLock<File> {
+		self.lock.as_lock()
+	}
+
+	/// Remove the file and release the lock.
+	#[inline]
+	pub fn release(self) -> Result<(), IoError> {
+		self.lock.unlock()
+	}
+}
+
+/// Read the pid of the file at the path (`None` if there is no file or pid).
+fn read_pid(path: &Path) -> Result<Option<FlockPid>, IoError> {
+	match File::open(path) {
+		Ok(file) => parse_pid(&file),
+		Err(e) if e.kind() == IoErrorKind::NotFound => Ok(None),
+		Err(e) => Err(e),
+	}
+}
+
+/// Read the pid from the beginning of the file (`None` if there is no pid).
+fn parse_pid(mut file: impl Read + Seek) -> Result<Option<FlockPid>, IoError> {
+	let mut data = String::new();
+	file.rewind()?;
+	file.read_to_string(&mut data)?;
+
+	Ok(data.trim().parse().ok())
+}
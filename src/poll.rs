@@ -0,0 +1,76 @@
+//! Waiting for a lock by repeating non-blocking attempts.
+//!
+//! Blocking `flock`/`fcntl` calls can be neither limited in time nor cancelled,
+//! so the lock is requested without waiting until it is set
+//! or the pause between attempts fails.
+
+use crate::element::FlockElement;
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Pause after the first unsuccessful attempt.
+const MIN_PAUSE: Duration = Duration::from_millis(1);
+/// The pause doubles after each attempt, but no more than this value.
+const MAX_PAUSE: Duration = Duration::from_millis(50);
+
+/// Repeat `try_lock` until the lock is set, `pause` fails
+/// or an error other than `WouldBlock` occurs.
+pub(crate) fn try_lock_loop<T, L>(
+	mut data: T,
+	try_lock: impl Fn(T) -> Result<L, FlockError<T>>,
+	mut pause: impl FnMut(Duration) -> Result<(), IoError>,
+) -> Result<L, FlockError<T>>
+where
+	T: FlockElement,
+{
+	let mut next_pause = MIN_PAUSE;
+	loop {
+		match try_lock(data) {
+			Ok(lock) => return Ok(lock),
+			Err(e) if e.is_would_block() => {
+				data = e.into_data();
+
+				if let Err(err) = pause(next_pause) {
+					return Err(FlockError::new(data, err));
+				}
+				next_pause = (next_pause * 2).min(MAX_PAUSE);
+			}
+			Err(e) => return Err(e),
+		}
+	}
+}
+
+/// Error returned when the lock was not set before the deadline.
+#[inline]
+fn timed_out_error() -> IoError {
+	IoError::new(
+		IoErrorKind::TimedOut,
+		"the lock was not acquired before the deadline",
+	)
+}
+
+/// Repeat `try_lock` until the lock is set, the deadline passes
+/// (`TimedOut`) or an error other than `WouldBlock` occurs.
+///
+/// At least one attempt is always made, even if the deadline has already passed.
+pub(crate) fn try_lock_until<T, L>(
+	data: T,
+	deadline: Instant,
+	try_lock: impl Fn(T) -> Result<L, FlockError<T>>,
+) -> Result<L, FlockError<T>>
+where
+	T: FlockElement,
+{
+	try_lock_loop(data, try_lock, |pause| {
+		let now = Instant::now();
+		if now >= deadline {
+			return Err(timed_out_error());
+		}
+
+		std::thread::sleep(pause.min(deadline - now));
+		Ok(())
+	})
+}
@@ -0,0 +1,435 @@
+//! Listing of the locks of a file from `/proc/locks` (and `/proc/self/fdinfo/<fd>`), Linux only.
+//!
+//! ```rust
+//! use cluFlock::proc_locks::list_file_locks;
+//! use cluFlock::ExclusiveFlock;
+//! use std::fs::File;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let file = File::create("./file")?;
+//! 	let file_lock = ExclusiveFlock::wait_lock(&file)?;
+//!
+//! 	for lock in list_file_locks(&file)? {
+//! 		// FLOCK WRITE pid 1234 bytes 0..
+//! 		println!("{}", lock);
+//! 	}
+//! 	drop(file_lock);
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::conflict::FlockPid;
+use crate::element::FlockElement;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::mode::FlockMode;
+use crate::sys::FlockRangePNum;
+use crate::sys::RawFilePtr;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::str::FromStr;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::string::String;
+use std::string::ToString;
+use std::vec::Vec;
+
+/// Path of the lock list of the whole system.
+pub const PROC_LOCKS_PATH: &str = "/proc/locks";
+
+/// Device (major, minor) and inode of a file, as shown in `/proc/locks`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProcFileId {
+	major: u32,
+	minor: u32,
+	inode: u64,
+}
+
+impl ProcFileId {
+	#[inline]
+	pub const fn new(major: u32, minor: u32, inode: u64) -> Self {
+		Self {
+			major,
+			minor,
+			inode,
+		}
+	}
+
+	/// Make from `st_dev` and `st_ino` of `stat`.
+	#[inline]
+	pub fn from_stat(dev: u64, inode: u64) -> Self {
+		let dev = dev as libc::dev_t;
+
+		Self::new(libc::major(dev) as _, libc::minor(dev) as _, inode)
+	}
+
+	#[inline(always)]
+	pub const fn major(&self) -> u32 {
+		self.major
+	}
+
+	#[inline(always)]
+	pub const fn minor(&self) -> u32 {
+		self.minor
+	}
+
+	#[inline(always)]
+	pub const fn inode(&self) -> u64 {
+		self.inode
+	}
+}
+
+impl Display for ProcFileId {
+	/// `major:minor:inode` (major and minor in hex), as in `/proc/locks`.
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		write!(f, "{:02x}:{:02x}:{}", self.major, self.minor, self.inode)
+	}
+}
+
+impl FromStr for ProcFileId {
+	type Err = IoError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut iter = s.splitn(3, ':');
+		let mut next = |radix| {
+			iter.next()
+				.and_then(|a| u64::from_str_radix(a, radix).ok())
+				.ok_or_else(|| invalid_data("invalid file id, expected major:minor:inode"))
+		};
+
+		Ok(Self::new(next(16)? as _, next(16)? as _, next(10)?))
+	}
+}
+
+/// Something whose locks can be found in `/proc/locks`.
+pub trait ProcLockTarget {
+	/// Get device and inode of the file.
+	fn proc_file_id(&self) -> Result<ProcFileId, IoError>;
+}
+
+impl<T> ProcLockTarget for T
+where
+	T: FlockElement,
+	T::FilePtr: Into<RawFilePtr>,
+{
+	fn proc_file_id(&self) -> Result<ProcFileId, IoError> {
+		let mut stat: libc::stat = unsafe { core::mem::zeroed() };
+
+		match unsafe { libc::fstat(self.as_file_ptr().into(), &mut stat) } {
+			-1 => Err(IoError::last_os_error()),
+			_ => Ok(ProcFileId::from_stat(stat.st_dev as _, stat.st_ino as _)),
+		}
+	}
+}
+
+impl ProcLockTarget for Path {
+	#[inline]
+	fn proc_file_id(&self) -> Result<ProcFileId, IoError> {
+		let metadata = std::fs::metadata(self)?;
+
+		Ok(ProcFileId::from_stat(metadata.dev(), metadata.ino()))
+	}
+}
+
+impl ProcLockTarget for PathBuf {
+	#[inline(always)]
+	fn proc_file_id(&self) -> Result<ProcFileId, IoError> {
+		self.as_path().proc_file_id()
+	}
+}
+
+impl ProcLockTarget for ProcFileId {
+	#[inline(always)]
+	fn proc_file_id(&self) -> Result<ProcFileId, IoError> {
+		Ok(*self)
+	}
+}
+
+/// Lock type (the first column of `/proc/locks`).
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProcLockKind {
+	/// `flock`
+	Flock,
+	/// POSIX `fcntl`
+	Posix,
+	/// OFD `fcntl`
+	Ofd,
+	/// LEASE, DELEG, ...
+	Other(String),
+}
+
+impl ProcLockKind {
+	/// Name as in `/proc/locks`.
+	pub fn as_str(&self) -> &str {
+		match self {
+			Self::Flock => "FLOCK",
+			Self::Posix => "POSIX",
+			Self::Ofd => "OFDLCK",
+			Self::Other(a) => a,
+		}
+	}
+}
+
+impl From<&str> for ProcLockKind {
+	fn from(s: &str) -> Self {
+		match s {
+			"FLOCK" => Self::Flock,
+			"POSIX" => Self::Posix,
+			"OFDLCK" => Self::Ofd,
+			a => Self::Other(a.to_string()),
+		}
+	}
+}
+
+/// Lock (or a waiter blocked on it) from `/proc/locks`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProcLock {
+	id: u64,
+	kind: ProcLockKind,
+	mode: Option<FlockMode>,
+	pid: Option<FlockPid>,
+	file_id: ProcFileId,
+	range: (FlockRangePNum, FlockRangePNum),
+	waiters: Vec<ProcLock>,
+}
+
+impl ProcLock {
+	/// Ordinal number of the lock in `/proc/locks`.
+	#[inline(always)]
+	pub const fn id(&self) -> u64 {
+		self.id
+	}
+
+	#[inline(always)]
+	pub const fn kind(&self) -> &ProcLockKind {
+		&self.kind
+	}
+
+	/// READ (Shared) or WRITE (Exclusive), `None` for other values (leases).
+	#[inline(always)]
+	pub const fn mode(&self) -> Option<FlockMode> {
+		self.mode
+	}
+
+	/// Process that set the lock, `None` for OFD locks.
+	#[inline(always)]
+	pub const fn pid(&self) -> Option<FlockPid> {
+		self.pid
+	}
+
+	#[inline(always)]
+	pub const fn file_id(&self) -> ProcFileId {
+		self.file_id
+	}
+
+	/// Locked bytes (A..=B), `B == MAX` for EOF.
+	#[inline(always)]
+	pub const fn range(&self) -> (FlockRangePNum, FlockRangePNum) {
+		self.range
+	}
+
+	/// Does the lock extend to the end of the file (EOF).
+	#[inline(always)]
+	pub fn is_to_end(&self) -> bool {
+		self.range.1 == FlockRangePNum::MAX
+	}
+
+	/// Locks blocked waiting for this lock (`->` entries).
+	#[inline(always)]
+	pub fn waiters(&self) -> &[ProcLock] {
+		&self.waiters
+	}
+
+	/// Parse one line of `/proc/locks`, `true` if it is a waiter (`->`).
+	fn parse(line: &str) -> Result<(bool, Self), IoError> {
+		// 1: POSIX  ADVISORY  WRITE 1234 08:01:1180 0 EOF
+		// 1: -> POSIX  ADVISORY  WRITE 1235 08:01:1180 0 EOF
+		let mut iter = line.split_whitespace();
+		let mut next = || {
+			iter.next()
+				.ok_or_else(|| invalid_data("unexpected end of the lock line"))
+		};
+
+		let id = next()?
+			.strip_suffix(':')
+			.and_then(|a| a.parse().ok())
+			.ok_or_else(|| invalid_data("invalid lock id"))?;
+		let mut kind = next()?;
+		let is_waiter = kind == "->";
+		if is_waiter {
+			kind = next()?;
+		}
+		let kind = ProcLockKind::from(kind);
+		let _advisory = next()?;
+		let mode = match next()? {
+			"READ" => Some(FlockMode::Shared),
+			"WRITE" => Some(FlockMode::Exclusive),
+			_ => None,
+		};
+		let pid = match next()?.parse::<FlockPid>() {
+			Ok(pid) if pid > 0 => Some(pid),
+			Ok(..) => None,
+			Err(..) => return Err(invalid_data("invalid lock pid")),
+		};
+		let file_id = next()?.parse()?;
+		let start = next()?
+			.parse()
+			.map_err(|_| invalid_data("invalid lock start"))?;
+		let end = match next()? {
+			"EOF" => FlockRangePNum::MAX,
+			a => a.parse().map_err(|_| invalid_data("invalid lock end"))?,
+		};
+
+		let lock = Self {
+			id,
+			kind,
+			mode,
+			pid,
+			file_id,
+			range: (start, end),
+			waiters: Vec::new(),
+		};
+		Ok((is_waiter, lock))
+	}
+}
+
+impl Display for ProcLock {
+	/// `POSIX WRITE pid 1234 bytes 0.. (1 waiting)`
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		let mode = match self.mode {
+			Some(FlockMode::Shared) => "READ",
+			Some(FlockMode::Exclusive) => "WRITE",
+			None => "?",
+		};
+		write!(f, "{} {}", self.kind.as_str(), mode)?;
+		match self.pid {
+			Some(pid) => write!(f, " pid {}", pid)?,
+			None => write!(f, " no pid")?,
+		}
+		match self.is_to_end() {
+			true => write!(f, " bytes {}..", self.range.0)?,
+			false => write!(f, " bytes {}..={}", self.range.0, self.range.1)?,
+		}
+		if !self.waiters.is_empty() {
+			write!(f, " ({} waiting)", self.waiters.len())?;
+		}
+
+		Ok(())
+	}
+}
+
+#[inline]
+fn invalid_data(info: &'static str) -> IoError {
+	IoError::new(IoErrorKind::InvalidData, info)
+}
+
+/// Parse the contents of `/proc/locks`, waiters (`->`) are attached to their locks.
+pub fn parse_proc_locks(text: &str) -> Result<Vec<ProcLock>, IoError> {
+	let mut result: Vec<ProcLock> = Vec::new();
+
+	for line in text.lines().filter(|a| !a.trim().is_empty()) {
+		let (is_waiter, lock) = ProcLock::parse(line)?;
+
+		match (is_waiter, result.last_mut()) {
+			(true, Some(holder)) if holder.id == lock.id => holder.waiters.push(lock),
+			(true, _) => return Err(invalid_data("waiter without a lock")),
+			(false, _) => result.push(lock),
+		}
+	}
+
+	Ok(result)
+}
+
+/// Parse the `lock:` lines of `/proc/<pid>/fdinfo/<fd>`.
+pub fn parse_fdinfo_locks(text: &str) -> Result<Vec<ProcLock>, IoError> {
+	let mut locks = String::new();
+	for line in text.lines() {
+		if let Some(lock) = line.strip_prefix("lock:") {
+			locks.push_str(lock);
+			locks.push('\n');
+		}
+	}
+
+	parse_proc_locks(&locks)
+}
+
+/// Read all locks of the system.
+#[inline]
+pub fn read_proc_locks() -> Result<Vec<ProcLock>, IoError> {
+	parse_proc_locks(&std::fs::read_to_string(PROC_LOCKS_PATH)?)
+}
+
+/// Read all locks (and their waiters) of the file (`&File`, `Path`, FlockElement).
+pub fn list_file_locks<F>(file: &F) -> Result<Vec<ProcLock>, IoError>
+where
+	F: ProcLockTarget + ?Sized,
+{
+	let file_id = file.proc_file_id()?;
+
+	let mut locks = read_proc_locks()?;
+	locks.retain(|a| a.file_id == file_id);
+
+	Ok(locks)
+}
+
+/// Read the locks set through the descriptor (`/proc/self/fdinfo/<fd>`).
+pub fn list_fd_locks<D>(data: &D) -> Result<Vec<ProcLock>, IoError>
+where
+	D: FlockElement,
+	D::FilePtr: Into<RawFilePtr>,
+{
+	let path = std::format!("/proc/self/fdinfo/{}", data.as_file_ptr().into());
+
+	parse_fdinfo_locks(&std::fs::read_to_string(path)?)
+}
+
+#[test]
+#[cfg(test)]
+fn test_parse_proc_locks() {
+	let locks = parse_proc_locks(
+		"1: POSIX  ADVISORY  WRITE 1234 08:01:1180 0 EOF\n\
+		1: -> POSIX  ADVISORY  WRITE 1235 08:01:1180 0 9\n\
+		1: -> POSIX  ADVISORY  READ  1236 08:01:1180 5 5\n\
+		2: OFDLCK ADVISORY  READ  -1 fe:00:77 10 19\n\
+		3: FLOCK  ADVISORY  WRITE 99 00:2b:5 0 EOF\n\
+		4: LEASE  ACTIVE    UNLCK 98 00:2b:6 0 EOF\n",
+	)
+	.unwrap();
+	assert_eq!(locks.len(), 4);
+
+	assert_eq!(locks[0].kind(), &ProcLockKind::Posix);
+	assert_eq!(locks[0].mode(), Some(FlockMode::Exclusive));
+	assert_eq!(locks[0].pid(), Some(1234));
+	assert_eq!(locks[0].file_id(), ProcFileId::new(8, 1, 1180));
+	assert!(locks[0].is_to_end());
+	assert_eq!(locks[0].waiters().len(), 2);
+	assert_eq!(locks[0].waiters()[0].pid(), Some(1235));
+	assert_eq!(locks[0].waiters()[0].range(), (0, 9));
+	assert_eq!(locks[0].waiters()[1].mode(), Some(FlockMode::Shared));
+	assert_eq!(
+		locks[0].to_string(),
+		"POSIX WRITE pid 1234 bytes 0.. (2 waiting)"
+	);
+
+	assert_eq!(locks[1].kind(), &ProcLockKind::Ofd);
+	assert_eq!(locks[1].pid(), None);
+	assert_eq!(locks[1].file_id(), ProcFileId::new(0xfe, 0, 77));
+	assert_eq!(locks[1].to_string(), "OFDLCK READ no pid bytes 10..=19");
+
+	assert_eq!(locks[2].kind(), &ProcLockKind::Flock);
+	assert_eq!(locks[3].kind(), &ProcLockKind::Other("LEASE".to_string()));
+	assert_eq!(locks[3].mode(), None);
+
+	let fdinfo_locks = parse_fdinfo_locks(
+		"pos:\t0\nflags:\t0100002\nmnt_id:\t29\nino:\t1180\n\
+		lock:\t1: FLOCK  ADVISORY  WRITE 1234 08:01:1180 0 EOF\n",
+	)
+	.unwrap();
+	assert_eq!(fdinfo_locks.len(), 1);
+	assert_eq!(fdinfo_locks[0].kind(), &ProcLockKind::Flock);
+
+	assert!(parse_proc_locks("1: -> POSIX  ADVISORY  WRITE 1 08:01:1 0 EOF").is_err());
+	assert!(parse_proc_locks("1: POSIX  ADVISORY  WRITE 1 08:01").is_err());
+}
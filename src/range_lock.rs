@@ -1,33 +1,82 @@
 use crate::element::FlockElement;
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::FlockMode;
+use crate::mode::Shared;
+use crate::r#macro::cfg_std;
+use crate::range::checker::FlockRangePNumBehChecker;
+use crate::range::pnum::FlockRangePNumBeh;
+use crate::range::FlockRange;
 use crate::range::FlockRangeFPrimitive;
 use crate::range::FlockRangeFull;
 use crate::sys::FlockRangePNum;
 use crate::unlock::WaitFlockUnlock;
 use crate::unlock::WaitFlockUnlockRange;
+use crate::ExclusiveFlockRange;
 use crate::FlockLock;
+use crate::SharedFlockRange;
+use core::fmt::Debug;
 use core::ops::Deref;
 use core::ops::DerefMut;
+use SafeManuallyDrop::ManuallyDrop;
 
 /// Type for securely creating and securely managing 'flock' locks.
 #[derive(/*Copy, */ Clone /*, Default*/, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct FlockRangeLock<T>
+pub struct FlockRangeLock<T, M = Exclusive>
 where
 	T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+	M: FlockLockMode,
 {
-	data: FlockLock<T>,
+	data: ManuallyDrop<FlockLock<T, M>>,
 	range: FlockRangeFPrimitive,
 }
 
-impl<T> FlockRangeLock<T>
+impl<T, M> Debug for FlockRangeLock<T, M>
+where
+	T: Debug + FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+	M: FlockLockMode,
+{
+	#[inline]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		f.debug_struct("FlockRangeLock")
+			.field("data", self.data.as_data_unchecked())
+			.field("range", &self.range)
+			.field("mode", &M::MODE)
+			.finish()
+	}
+}
+
+impl<T, M> FlockRangeLock<T, M>
 where
 	T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+	M: FlockLockMode,
 {
 	/// Form from an already existing flock with the required range.
 	#[inline]
-	pub unsafe fn from_flock(data: FlockLock<T>, range: impl Into<FlockRangeFPrimitive>) -> Self {
+	pub unsafe fn from_flock(
+		data: FlockLock<T, M>,
+		range: impl Into<FlockRangeFPrimitive>,
+	) -> Self {
 		let range = range.into();
 
-		Self { data, range }
+		Self {
+			data: ManuallyDrop::new(data),
+			range,
+		}
+	}
+
+	/// Lock mode of the guard.
+	#[inline(always)]
+	pub const fn mode(&self) -> FlockMode {
+		M::MODE
+	}
+
+	/// Get the locked range.
+	#[inline(always)]
+	pub const fn get_range(&self) -> FlockRangeFPrimitive {
+		self.range
 	}
 
 	/// Is FlockRangeLock a wrapper with values, or is it actually a transparent value with no false data.
@@ -35,11 +84,127 @@ where
 	pub const fn is_repr_transparent(&self) -> bool {
 		false
 	}
+
+	/// Destroy the range lock, return a good result or error.
+	#[inline]
+	pub fn unlock_fn<R>(self, next: impl FnOnce() -> R, errf: impl FnOnce(IoError) -> R) -> R {
+		let (data, result) = self.unlock_data_fn(next, errf);
+		drop(data);
+
+		result
+	}
+
+	/// Destroy the range lock, return a good result or error.
+	#[inline]
+	pub fn unlock(self) -> Result<(), IoError> {
+		self.unlock_fn(|| Ok(()), Err)
+	}
+
+	/// Destroy the range lock, return data and error data.
+	#[inline]
+	pub fn unlock_data(self) -> (T, Result<(), IoError>) {
+		self.unlock_data_fn(|| Ok(()), Err)
+	}
+
+	/// Destroy the range lock, return data and error data.
+	#[inline]
+	pub fn unlock_data_fn<R>(
+		mut self,
+		next: impl FnOnce() -> R,
+		errf: impl FnOnce(IoError) -> R,
+	) -> (T, R) {
+		unsafe {
+			let range = self.range; // copy
+			let result = WaitFlockUnlockRange::unlock_range_fn(
+				self.data.as_mut_data_unchecked(),
+				range,
+				next,
+				errf,
+			);
+			let data = ManuallyDrop::take(&mut self.data).ignore_unlock();
+			ManuallyDrop::forget(self);
+
+			(data, result)
+		}
+	}
+}
+
+impl<T> FlockRangeLock<T, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+{
+	/// Expect to get an exclusive range lock or get an error right away.
+	#[inline(always)]
+	pub fn wait_exclusive_lock<NS, NE, C>(
+		data: T,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<FlockRangeLock<T, Exclusive>, FlockError<T>>
+	where
+		T: ExclusiveFlockRange,
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		ExclusiveFlockRange::wait_lock_range(data, range)
+	}
+
+	/// Get an exclusive range lock without waiting (if there was no lock before)
+	/// or get an error right away.
+	#[inline(always)]
+	pub fn try_exclusive_lock<NS, NE, C>(
+		data: T,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<FlockRangeLock<T, Exclusive>, FlockError<T>>
+	where
+		T: ExclusiveFlockRange,
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		ExclusiveFlockRange::try_lock_range(data, range)
+	}
 }
 
-impl<T> Default for FlockRangeLock<T>
+impl<T> FlockRangeLock<T, Shared>
+where
+	T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+{
+	/// Expect to get an shared range lock or get an error right away.
+	#[inline(always)]
+	pub fn wait_shared_lock<NS, NE, C>(
+		data: T,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<FlockRangeLock<T, Shared>, FlockError<T>>
+	where
+		T: SharedFlockRange,
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		SharedFlockRange::wait_lock_range(data, range)
+	}
+
+	/// Get an shared range lock without waiting (if there was no lock before)
+	/// or get an error right away.
+	#[inline(always)]
+	pub fn try_shared_lock<NS, NE, C>(
+		data: T,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<FlockRangeLock<T, Shared>, FlockError<T>>
+	where
+		T: SharedFlockRange,
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		SharedFlockRange::try_lock_range(data, range)
+	}
+}
+
+impl<T, M> Default for FlockRangeLock<T, M>
 where
 	T: Default + FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+	M: FlockLockMode + Default,
 {
 	#[inline]
 	fn default() -> Self {
@@ -52,11 +217,12 @@ where
 	}
 }
 
-impl<T> Deref for FlockRangeLock<T>
+impl<T, M> Deref for FlockRangeLock<T, M>
 where
 	T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+	M: FlockLockMode,
 {
-	type Target = FlockLock<T>;
+	type Target = FlockLock<T, M>;
 
 	#[inline(always)]
 	fn deref(&self) -> &Self::Target {
@@ -64,9 +230,10 @@ where
 	}
 }
 
-impl<T> DerefMut for FlockRangeLock<T>
+impl<T, M> DerefMut for FlockRangeLock<T, M>
 where
 	T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+	M: FlockLockMode,
 {
 	#[inline(always)]
 	fn deref_mut(&mut self) -> &mut Self::Target {
@@ -74,7 +241,7 @@ where
 	}
 }
 
-impl<T> AsRef<T> for FlockRangeLock<T>
+impl<T> AsRef<T> for FlockRangeLock<T, Exclusive>
 where
 	T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
 {
@@ -84,7 +251,7 @@ where
 	}
 }
 
-impl<T> AsMut<T> for FlockRangeLock<T>
+impl<T> AsMut<T> for FlockRangeLock<T, Exclusive>
 where
 	T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
 {
@@ -94,18 +261,191 @@ where
 	}
 }
 
-impl<T> Drop for FlockRangeLock<T>
+cfg_std! {
+	if #std {
+		use std::io::BufRead;
+		use std::io::IoSliceMut;
+		use std::io::IoSlice;
+		use std::io::Read;
+		use std::io::Seek;
+		use std::io::SeekFrom;
+		use std::io::Write;
+
+		/// Read access is available under any lock (the range is not checked).
+		impl<T, M> Read for FlockRangeLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + Read,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+				self.data.read(buf)
+			}
+
+			#[inline(always)]
+			fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize, IoError> {
+				self.data.read_vectored(bufs)
+			}
+		}
+
+		/// Seek is available under any lock.
+		impl<T, M> Seek for FlockRangeLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + Seek,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn seek(&mut self, pos: SeekFrom) -> Result<u64, IoError> {
+				self.data.seek(pos)
+			}
+		}
+
+		/// Write access is available only under an exclusive lock (the range is not checked).
+		impl<T> Write for FlockRangeLock<T, Exclusive>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + Write,
+		{
+			#[inline(always)]
+			fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+				self.data.write(buf)
+			}
+
+			#[inline(always)]
+			fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize, IoError> {
+				self.data.write_vectored(bufs)
+			}
+
+			#[inline(always)]
+			fn flush(&mut self) -> Result<(), IoError> {
+				self.data.flush()
+			}
+		}
+
+		/// Buffered read access is available under any lock.
+		impl<T, M> BufRead for FlockRangeLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + BufRead,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn fill_buf(&mut self) -> Result<&[u8], IoError> {
+				self.data.fill_buf()
+			}
+
+			#[inline(always)]
+			fn consume(&mut self, amt: usize) {
+				self.data.consume(amt)
+			}
+		}
+
+		/// Read access through a shared link, if `&T` is `Read` (`&File`).
+		impl<T, M> Read for &FlockRangeLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+			for<'a> &'a T: Read,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+				self.data.as_data_unchecked().read(buf)
+			}
+		}
+
+		/// Seek through a shared link, if `&T` is `Seek` (`&File`).
+		impl<T, M> Seek for &FlockRangeLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+			for<'a> &'a T: Seek,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn seek(&mut self, pos: SeekFrom) -> Result<u64, IoError> {
+				self.data.as_data_unchecked().seek(pos)
+			}
+		}
+
+		/// Write access through a shared link of an exclusive lock, if `&T` is `Write` (`&File`).
+		impl<T> Write for &FlockRangeLock<T, Exclusive>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+			for<'a> &'a T: Write,
+		{
+			#[inline(always)]
+			fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+				self.data.as_data_unchecked().write(buf)
+			}
+
+			#[inline(always)]
+			fn flush(&mut self) -> Result<(), IoError> {
+				self.data.as_data_unchecked().flush()
+			}
+		}
+
+		/// The descriptor of the data (`IsTerminal` and other descriptor-based APIs).
+		#[cfg(unix)]
+		impl<T, M> std::os::unix::io::AsFd for FlockRangeLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + std::os::unix::io::AsFd,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn as_fd(&self) -> std::os::unix::io::BorrowedFd<'_> {
+				self.data.as_data_unchecked().as_fd()
+			}
+		}
+
+		#[cfg(unix)]
+		impl<T, M> std::os::unix::io::AsRawFd for FlockRangeLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + std::os::unix::io::AsRawFd,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+				self.data.as_data_unchecked().as_raw_fd()
+			}
+		}
+
+		/// The handle of the data (`IsTerminal` and other handle-based APIs).
+		#[cfg(windows)]
+		impl<T, M> std::os::windows::io::AsHandle for FlockRangeLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + std::os::windows::io::AsHandle,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn as_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
+				self.data.as_data_unchecked().as_handle()
+			}
+		}
+
+		#[cfg(windows)]
+		impl<T, M> std::os::windows::io::AsRawHandle for FlockRangeLock<T, M>
+		where
+			T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange + std::os::windows::io::AsRawHandle,
+			M: FlockLockMode,
+		{
+			#[inline(always)]
+			fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
+				self.data.as_data_unchecked().as_raw_handle()
+			}
+		}
+	}
+}
+
+impl<T, M> Drop for FlockRangeLock<T, M>
 where
 	T: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+	M: FlockLockMode,
 {
 	fn drop(&mut self) {
 		unsafe {
 			// Forget unlocking from FlockLock and perform our own unlocking.
 			//
 			let range = self.range; // copy
-			WaitFlockUnlockRange::unlock_range_no_result(self.as_mut_data(), range);
+			WaitFlockUnlockRange::unlock_range_no_result(self.data.as_mut_data_unchecked(), range);
 
-			self.data.nomove_ignore_unlock_no_result();
+			ManuallyDrop::take(&mut self.data).ignore_unlock_no_result();
 		}
 	}
 }
@@ -0,0 +1,252 @@
+//! Reentrant (recursive) locks of files by path (Unix only).
+//!
+//! Acquisitions are counted per thread per file (dev, inode): a nested
+//! acquisition in the same thread does not wait for itself, and `LOCK_UN`
+//! is issued only when the last guard of the thread is dropped. An exclusive
+//! acquisition inside a shared one is rejected (`Deadlock`), flock conversion
+//! is not atomic.
+//!
+//! ```rust
+//! use cluFlock::reentrant::ReentrantFlock;
+//!
+//! fn save() -> Result<(), std::io::Error> {
+//! 	let lock = ReentrantFlock::wait_exclusive_lock("./test_file")?;
+//! 	println!("{:?}", lock); // the caller's lock is reused
+//!
+//! 	Ok(())
+//! } // the file is still locked by the caller
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let lock = ReentrantFlock::wait_exclusive_lock("./test_file")?;
+//! 	save()?;
+//!
+//! 	drop(lock); // LOCK_UN
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::FlockMode;
+use crate::mode::Shared;
+use crate::ExclusiveFlock;
+use crate::FlockLock;
+use crate::FlockReadOnly;
+use crate::SharedFlock;
+use core::cell::RefCell;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::marker::PhantomData;
+use std::collections::HashMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::rc::Rc;
+
+/// Identity of a file (dev, inode).
+type FileKey = (u64, u64);
+
+/// Lock held by the thread.
+#[derive(Debug)]
+enum HeldLock {
+	Shared(FlockLock<File, Shared>),
+	Exclusive(FlockLock<File, Exclusive>),
+}
+
+impl HeldLock {
+	#[inline]
+	fn as_file(&self) -> &File {
+		match self {
+			Self::Shared(lock) => lock.as_data_unchecked(),
+			Self::Exclusive(lock) => lock.as_data(),
+		}
+	}
+
+	#[inline]
+	const fn mode(&self) -> FlockMode {
+		match self {
+			Self::Shared(..) => FlockMode::Shared,
+			Self::Exclusive(..) => FlockMode::Exclusive,
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Held {
+	count: usize,
+	lock: Rc<HeldLock>,
+}
+
+std::thread_local! {
+	static HELD: RefCell<HashMap<FileKey, Held>> = RefCell::new(HashMap::new());
+}
+
+/// Reentrant lock of the file at the path, the guard stays in its thread.
+pub struct ReentrantFlock<M = Exclusive>
+where
+	M: FlockLockMode,
+{
+	key: FileKey,
+	lock: Rc<HeldLock>,
+	_mode: PhantomData<M>,
+}
+
+impl ReentrantFlock<Exclusive> {
+	/// Expect to get an exclusive lock of the file at the path
+	/// (`Deadlock` if the thread holds a shared lock of it).
+	#[inline]
+	pub fn wait_exclusive_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), |file| {
+			ExclusiveFlock::wait_lock(file).map(HeldLock::Exclusive)
+		})
+	}
+
+	/// Get an exclusive lock of the file at the path without waiting
+	/// or get an error right away (`WouldBlock` if it is locked).
+	#[inline]
+	pub fn try_exclusive_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), |file| {
+			ExclusiveFlock::try_lock(file).map(HeldLock::Exclusive)
+		})
+	}
+
+	/// Get a link to the file of the first acquisition.
+	#[inline(always)]
+	pub fn as_file(&self) -> &File {
+		self.lock.as_file()
+	}
+}
+
+impl ReentrantFlock<Shared> {
+	/// Expect to get an shared lock of the file at the path
+	/// (an exclusive lock of the thread is also suitable).
+	#[inline]
+	pub fn wait_shared_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), |file| {
+			SharedFlock::wait_lock(file).map(HeldLock::Shared)
+		})
+	}
+
+	/// Get an shared lock of the file at the path without waiting
+	/// or get an error right away (`WouldBlock` if it is locked).
+	#[inline]
+	pub fn try_shared_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), |file| {
+			SharedFlock::try_lock(file).map(HeldLock::Shared)
+		})
+	}
+
+	/// Get a read-only link to the file of the first acquisition.
+	#[inline(always)]
+	pub fn as_file(&self) -> &FlockReadOnly<File> {
+		FlockReadOnly::from_ref(self.lock.as_file())
+	}
+}
+
+impl<M> ReentrantFlock<M>
+where
+	M: FlockLockMode,
+{
+	/// Open (create) the file and lock it, or reuse the lock of the thread.
+	fn open_lock(
+		path: &Path,
+		lock: impl FnOnce(File) -> Result<HeldLock, FlockError<File>>,
+	) -> Result<Self, IoError> {
+		let file = OpenOptions::new()
+			.read(true)
+			.write(true)
+			.create(true)
+			.truncate(false)
+			.open(path)?;
+		let meta = file.metadata()?;
+		let key = (meta.dev(), meta.ino());
+
+		let held = HELD.with(|held| {
+			let mut held = held.borrow_mut();
+			let held = held.get_mut(&key)?;
+			Some(match (held.lock.mode(), M::MODE) {
+				(FlockMode::Shared, FlockMode::Exclusive) => Err(IoError::new(
+					IoErrorKind::Deadlock,
+					"exclusive lock inside a shared lock of the same thread",
+				)),
+				_ => {
+					held.count += 1;
+					Ok(held.lock.clone())
+				}
+			})
+		});
+		let lock = match held {
+			Some(held) => held?,
+			None => {
+				let lock = Rc::new(lock(file).map_err(FlockError::into_err)?);
+				let held = Held {
+					count: 1,
+					lock: lock.clone(),
+				};
+				HELD.with(|h| h.borrow_mut().insert(key, held));
+
+				lock
+			}
+		};
+
+		Ok(Self {
+			key,
+			lock,
+			_mode: PhantomData,
+		})
+	}
+
+	/// Get the mode of the guard.
+	#[inline(always)]
+	pub const fn mode(&self) -> FlockMode {
+		M::MODE
+	}
+
+	/// Get the mode of the flock lock held by the thread
+	/// (exclusive for a shared guard inside an exclusive one).
+	#[inline(always)]
+	pub fn held_mode(&self) -> FlockMode {
+		self.lock.mode()
+	}
+
+	/// Number of guards of the file in the thread.
+	#[inline]
+	pub fn count(&self) -> usize {
+		HELD.with(|held| held.borrow().get(&self.key).map_or(0, |held| held.count))
+	}
+}
+
+impl<M> Debug for ReentrantFlock<M>
+where
+	M: FlockLockMode,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("ReentrantFlock")
+			.field("lock", &self.lock)
+			.field("mode", &M::MODE)
+			.finish()
+	}
+}
+
+impl<M> Drop for ReentrantFlock<M>
+where
+	M: FlockLockMode,
+{
+	fn drop(&mut self) {
+		// The last guard of the thread removes the lock, it is unlocked
+		// when `self.lock` is dropped.
+		let _e = HELD.try_with(|held| {
+			let mut held = held.borrow_mut();
+			if let Some(a) = held.get_mut(&self.key) {
+				a.count -= 1;
+				if a.count == 0 {
+					held.remove(&self.key);
+				}
+			}
+		});
+	}
+}
@@ -0,0 +1,326 @@
+//! Process-wide registry of locks: the same shared/exclusive semantics
+//! between threads as between processes (Unix only).
+//!
+//! flock locks belong to the open file description: threads that open the same
+//! path separately conflict, but threads sharing one `&File` do not. The registry
+//! keeps one descriptor per file (dev, inode) and combines an in-process
+//! read-write lock with one flock lock of that descriptor. The flock lock is
+//! taken by the first holder in the process and released by the last one.
+//!
+//! ```rust
+//! use cluFlock::registry::RegistryLock;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	std::thread::scope(|s| {
+//! 		for _ in 0..4 {
+//! 			s.spawn(|| {
+//! 				let lock = RegistryLock::wait_exclusive_lock("./test_file")?;
+//! 				println!("{:?}", lock); // one thread at a time, one process at a time
+//!
+//! 				Ok::<(), std::io::Error>(())
+//! 			});
+//! 		}
+//! 	});
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::FlockMode;
+use crate::mode::Shared;
+use crate::rawfile::GetRawFile;
+use crate::rawfile::RawFile;
+use crate::ExclusiveFlock;
+use crate::FlockLock;
+use crate::FlockReadOnly;
+use crate::SharedFlock;
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::marker::PhantomData;
+use std::collections::HashMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::OnceLock;
+use std::sync::PoisonError;
+use std::sync::Weak;
+
+/// Identity of a file (dev, inode).
+type FileKey = (u64, u64);
+
+fn registry() -> MutexGuard<'static, HashMap<FileKey, Weak<Entry>>> {
+	static REGISTRY: OnceLock<Mutex<HashMap<FileKey, Weak<Entry>>>> = OnceLock::new();
+
+	REGISTRY
+		.get_or_init(Default::default)
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner)
+}
+
+/// flock lock of the registered descriptor.
+#[derive(Debug)]
+enum KernelLock {
+	Shared(FlockLock<RawFile, Shared>),
+	Exclusive(FlockLock<RawFile, Exclusive>),
+}
+
+#[derive(Debug, Default)]
+struct State {
+	readers: usize,
+	is_writer: bool,
+	/// A thread is waiting for the flock lock.
+	is_kernel_busy: bool,
+	kernel: Option<KernelLock>,
+}
+
+/// Registered file.
+#[derive(Debug)]
+struct Entry {
+	key: FileKey,
+	state: Mutex<State>,
+	condvar: Condvar,
+	// Dropped after the kernel lock.
+	file: File,
+}
+
+impl Entry {
+	fn state(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	/// Get the registered file (register `file` if it is not registered).
+	fn get_or_register(file: File) -> Result<Arc<Self>, IoError> {
+		let meta = file.metadata()?;
+		let key = (meta.dev(), meta.ino());
+
+		let mut registry = registry();
+		if let Some(entry) = registry.get(&key).and_then(Weak::upgrade) {
+			return Ok(entry);
+		}
+		let entry = Arc::new(Self {
+			key,
+			state: Mutex::new(State::default()),
+			condvar: Condvar::new(),
+			file,
+		});
+		registry.insert(key, Arc::downgrade(&entry));
+
+		Ok(entry)
+	}
+
+	fn lock(&self, mode: FlockMode, is_wait: bool) -> Result<(), IoError> {
+		let mut state = self.state();
+		loop {
+			let is_free = match mode {
+				_ if state.is_kernel_busy => false,
+				FlockMode::Shared if !state.is_writer && state.readers > 0 => {
+					// The flock lock is already shared.
+					state.readers += 1;
+					return Ok(());
+				}
+				FlockMode::Shared => !state.is_writer,
+				FlockMode::Exclusive => !state.is_writer && state.readers == 0,
+			};
+			if is_free {
+				break;
+			}
+			if !is_wait {
+				return Err(IoErrorKind::WouldBlock.into());
+			}
+			state = self
+				.condvar
+				.wait(state)
+				.unwrap_or_else(PoisonError::into_inner);
+		}
+
+		// The first holder, waits for the flock lock without blocking the registry.
+		state.is_kernel_busy = true;
+		drop(state);
+		let kernel = self.kernel_lock(mode, is_wait);
+
+		let mut state = self.state();
+		state.is_kernel_busy = false;
+		let result = kernel.map(|kernel| {
+			match mode {
+				FlockMode::Shared => state.readers = 1,
+				FlockMode::Exclusive => state.is_writer = true,
+			}
+			state.kernel = Some(kernel);
+		});
+		self.condvar.notify_all();
+
+		result
+	}
+
+	fn kernel_lock(&self, mode: FlockMode, is_wait: bool) -> Result<KernelLock, IoError> {
+		// The file lives as long as the entry, the kernel lock is released before it.
+		let raw = unsafe { self.file.get_raw_file() };
+
+		let kernel = match (mode, is_wait) {
+			(FlockMode::Shared, true) => SharedFlock::wait_lock(raw).map(KernelLock::Shared),
+			(FlockMode::Shared, false) => SharedFlock::try_lock(raw).map(KernelLock::Shared),
+			(FlockMode::Exclusive, true) => {
+				ExclusiveFlock::wait_lock(raw).map(KernelLock::Exclusive)
+			}
+			(FlockMode::Exclusive, false) => {
+				ExclusiveFlock::try_lock(raw).map(KernelLock::Exclusive)
+			}
+		};
+
+		kernel.map_err(|e| e.into_err())
+	}
+
+	fn unlock(&self, mode: FlockMode) -> Result<(), IoError> {
+		let mut state = self.state();
+		match mode {
+			FlockMode::Shared => state.readers -= 1,
+			FlockMode::Exclusive => state.is_writer = false,
+		}
+
+		// The last holder releases the flock lock.
+		let result = match (state.is_writer, state.readers) {
+			(false, 0) => match state.kernel.take() {
+				Some(KernelLock::Shared(lock)) => lock.unlock(),
+				Some(KernelLock::Exclusive(lock)) => lock.unlock(),
+				None => Ok(()),
+			},
+			_ => Ok(()),
+		};
+		self.condvar.notify_all();
+
+		result
+	}
+}
+
+impl Drop for Entry {
+	fn drop(&mut self) {
+		let mut registry = registry();
+		// The key could already be registered again by another file.
+		if registry
+			.get(&self.key)
+			.is_some_and(|entry| entry.strong_count() == 0)
+		{
+			registry.remove(&self.key);
+		}
+	}
+}
+
+/// Lock of the file at the path, coordinated between the threads of the process
+/// and between processes.
+pub struct RegistryLock<M = Exclusive>
+where
+	M: FlockLockMode,
+{
+	entry: Arc<Entry>,
+	_mode: PhantomData<M>,
+}
+
+impl RegistryLock<Exclusive> {
+	/// Expect to get an exclusive lock of the file at the path.
+	#[inline]
+	pub fn wait_exclusive_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), true)
+	}
+
+	/// Get an exclusive lock of the file at the path without waiting
+	/// or get an error right away (`WouldBlock` if it is locked).
+	#[inline]
+	pub fn try_exclusive_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), false)
+	}
+
+	/// Get a link to the registered file (one for all holders in the process).
+	#[inline(always)]
+	pub fn as_file(&self) -> &File {
+		&self.entry.file
+	}
+}
+
+impl RegistryLock<Shared> {
+	/// Expect to get an shared lock of the file at the path.
+	#[inline]
+	pub fn wait_shared_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), true)
+	}
+
+	/// Get an shared lock of the file at the path without waiting
+	/// or get an error right away (`WouldBlock` if it is locked).
+	#[inline]
+	pub fn try_shared_lock(path: impl AsRef<Path>) -> Result<Self, IoError> {
+		Self::open_lock(path.as_ref(), false)
+	}
+
+	/// Get a read-only link to the registered file (one for all holders in the process).
+	#[inline(always)]
+	pub fn as_file(&self) -> &FlockReadOnly<File> {
+		FlockReadOnly::from_ref(&self.entry.file)
+	}
+}
+
+impl<M> RegistryLock<M>
+where
+	M: FlockLockMode,
+{
+	/// Open (create) the file and lock it through the registry.
+	fn open_lock(path: &Path, is_wait: bool) -> Result<Self, IoError> {
+		let file = OpenOptions::new()
+			.read(true)
+			.write(true)
+			.create(true)
+			.truncate(false)
+			.open(path)?;
+		let entry = Entry::get_or_register(file)?;
+		entry.lock(M::MODE, is_wait)?;
+
+		Ok(Self {
+			entry,
+			_mode: PhantomData,
+		})
+	}
+
+	/// Get the lock mode.
+	#[inline(always)]
+	pub const fn mode(&self) -> FlockMode {
+		M::MODE
+	}
+
+	/// Release the lock (the flock lock is released by the last holder).
+	#[inline]
+	pub fn unlock(self) -> Result<(), IoError> {
+		let entry = unsafe { core::ptr::read(&self.entry) };
+		core::mem::forget(self);
+
+		entry.unlock(M::MODE)
+	}
+}
+
+impl<M> Debug for RegistryLock<M>
+where
+	M: FlockLockMode,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("RegistryLock")
+			.field("file", &self.entry.file)
+			.field("mode", &M::MODE)
+			.finish()
+	}
+}
+
+impl<M> Drop for RegistryLock<M>
+where
+	M: FlockLockMode,
+{
+	#[inline]
+	fn drop(&mut self) {
+		let _e = self.entry.unlock(M::MODE);
+	}
+}
@@ -0,0 +1,317 @@
+//! Repeating non-blocking lock attempts with backoff (`try_lock_retry`).
+//!
+//! ```rust
+//! use cluFlock::retry::RetryPolicy;
+//! use cluFlock::ExclusiveFlock;
+//! use std::fs::File;
+//! use std::time::Duration;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let file = File::create("./file")?;
+//!
+//! 	let policy = RetryPolicy::decorrelated_jitter(Duration::from_millis(10), Duration::from_secs(1))
+//! 		.max_attempts(20)
+//! 		.max_elapsed(Duration::from_secs(10))
+//! 		.on_retry(|stats, pause| println!("{}, next attempt in {:?}", stats, pause));
+//!
+//! 	match ExclusiveFlock::try_lock_retry(&file, policy) {
+//! 		Ok(lock) => println!("{:?}", lock),
+//! 		Err(e) => {
+//! 			// e.is_would_block() == true
+//! 			println!("{:?}", e.retry_stats());
+//! 			return Err(e.into_err());
+//! 		}
+//! 	}
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::future::FlockBackoff;
+use core::fmt::Debug;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use std::boxed::Box;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Pauses between attempts.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RetryBackoff {
+	/// The same pause.
+	Fixed(Duration),
+	/// The pause starts with `initial` and doubles after each attempt, but no more than `max`.
+	Exponential { initial: Duration, max: Duration },
+	/// Random pause between `base` and three times the previous pause, but no more than `max`
+	/// ("decorrelated jitter", spreads out the attempts of competing processes).
+	DecorrelatedJitter { base: Duration, max: Duration },
+}
+
+/// Statistics of the attempts to get a lock.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RetryStats {
+	attempts: u32,
+	elapsed: Duration,
+}
+
+impl RetryStats {
+	/// Number of unsuccessful attempts.
+	#[inline(always)]
+	pub const fn attempts(&self) -> u32 {
+		self.attempts
+	}
+
+	/// Time since the first attempt.
+	#[inline(always)]
+	pub const fn elapsed(&self) -> Duration {
+		self.elapsed
+	}
+}
+
+impl Display for RetryStats {
+	#[inline]
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		write!(f, "{} attempts in {:?}", self.attempts, self.elapsed)
+	}
+}
+
+/// The attempts were stopped by `RetryPolicy`
+/// (the source of the final `WouldBlock` error, see `FlockError::retry_stats`).
+#[derive(Debug)]
+pub struct FlockRetryError {
+	stats: RetryStats,
+	err: IoError,
+}
+
+impl FlockRetryError {
+	/// Statistics of the attempts.
+	#[inline(always)]
+	pub const fn stats(&self) -> RetryStats {
+		self.stats
+	}
+
+	/// Error of the last attempt.
+	#[inline(always)]
+	pub const fn as_err(&self) -> &IoError {
+		&self.err
+	}
+}
+
+impl Display for FlockRetryError {
+	#[inline]
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		write!(f, "the lock was not acquired, {}: {}", self.stats, self.err)
+	}
+}
+
+impl std::error::Error for FlockRetryError {
+	#[inline]
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.err)
+	}
+}
+
+/// Callback before each pause (the statistics, the pause).
+type OnRetry = Box<dyn FnMut(&RetryStats, Duration) + Send>;
+
+/// Policy of repeating non-blocking attempts to get a lock.
+///
+/// Is also a `FlockBackoff` for `FlockFuture`.
+pub struct RetryPolicy {
+	backoff: RetryBackoff,
+	max_attempts: Option<u32>,
+	max_elapsed: Option<Duration>,
+	on_retry: Option<OnRetry>,
+
+	start: Option<Instant>,
+	stats: RetryStats,
+	last_pause: Duration,
+	random: u64,
+}
+
+impl RetryPolicy {
+	/// Endless attempts with the `backoff` pauses.
+	pub fn new(backoff: RetryBackoff) -> Self {
+		let random = RandomState::new().build_hasher().finish();
+
+		Self {
+			backoff,
+			max_attempts: None,
+			max_elapsed: None,
+			on_retry: None,
+
+			start: None,
+			stats: RetryStats::default(),
+			last_pause: Duration::ZERO,
+			random: random | 1,
+		}
+	}
+
+	/// Endless attempts with the same pause.
+	#[inline]
+	pub fn fixed(pause: Duration) -> Self {
+		Self::new(RetryBackoff::Fixed(pause))
+	}
+
+	/// Endless attempts with a doubling pause.
+	#[inline]
+	pub fn exponential(initial: Duration, max: Duration) -> Self {
+		Self::new(RetryBackoff::Exponential { initial, max })
+	}
+
+	/// Endless attempts with a random pause (decorrelated jitter).
+	#[inline]
+	pub fn decorrelated_jitter(base: Duration, max: Duration) -> Self {
+		Self::new(RetryBackoff::DecorrelatedJitter { base, max })
+	}
+
+	/// Stop after `max` unsuccessful attempts.
+	#[inline]
+	pub fn max_attempts(mut self, max: u32) -> Self {
+		self.max_attempts = Some(max);
+		self
+	}
+
+	/// Stop when `max` has elapsed since the first attempt.
+	#[inline]
+	pub fn max_elapsed(mut self, max: Duration) -> Self {
+		self.max_elapsed = Some(max);
+		self
+	}
+
+	/// Call `f` before each pause (statistics and the pause).
+	#[inline]
+	pub fn on_retry(mut self, f: impl FnMut(&RetryStats, Duration) + Send + 'static) -> Self {
+		self.on_retry = Some(Box::new(f));
+		self
+	}
+
+	/// Pause settings.
+	#[inline(always)]
+	pub const fn backoff(&self) -> RetryBackoff {
+		self.backoff
+	}
+
+	/// Statistics of the attempts made.
+	#[inline(always)]
+	pub const fn stats(&self) -> RetryStats {
+		self.stats
+	}
+
+	/// xorshift64, only for jitter.
+	fn next_random(&mut self) -> u64 {
+		self.random ^= self.random << 13;
+		self.random ^= self.random >> 7;
+		self.random ^= self.random << 17;
+
+		self.random
+	}
+
+	fn make_pause(&mut self) -> Duration {
+		let pause = match self.backoff {
+			RetryBackoff::Fixed(pause) => pause,
+			RetryBackoff::Exponential { initial, max } => match self.stats.attempts {
+				0 | 1 => initial,
+				_ => self.last_pause.saturating_mul(2),
+			}
+			.min(max),
+			RetryBackoff::DecorrelatedJitter { base, max } => {
+				let high = self.last_pause.saturating_mul(3).max(base);
+				let spread = (high - base).as_nanos().min(u64::MAX as u128) as u64;
+				let random = match spread {
+					0 => 0,
+					spread => self.next_random() % spread,
+				};
+
+				(base + Duration::from_nanos(random)).min(max)
+			}
+		};
+		self.last_pause = pause;
+
+		pause
+	}
+
+	/// Time of the first attempt (for the attempts made by `try_lock_retry`).
+	#[inline]
+	fn start(&mut self) -> Instant {
+		*self.start.get_or_insert_with(Instant::now)
+	}
+}
+
+impl FlockBackoff for RetryPolicy {
+	fn next_pause(&mut self) -> Option<Duration> {
+		self.stats.attempts += 1;
+		self.stats.elapsed = self.start().elapsed();
+
+		if let Some(max_attempts) = self.max_attempts {
+			if self.stats.attempts >= max_attempts {
+				return None;
+			}
+		}
+
+		let mut pause = self.make_pause();
+		if let Some(max_elapsed) = self.max_elapsed {
+			match max_elapsed.checked_sub(self.stats.elapsed) {
+				Some(left) if !left.is_zero() => pause = pause.min(left),
+				_ => return None,
+			}
+		}
+
+		if let Some(on_retry) = self.on_retry.as_mut() {
+			on_retry(&self.stats, pause);
+		}
+
+		Some(pause)
+	}
+}
+
+impl Debug for RetryPolicy {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("RetryPolicy")
+			.field("backoff", &self.backoff)
+			.field("max_attempts", &self.max_attempts)
+			.field("max_elapsed", &self.max_elapsed)
+			.field("on_retry", &self.on_retry.is_some())
+			.field("stats", &self.stats)
+			.finish()
+	}
+}
+
+/// Repeat `try_lock` according to `policy`, the final `WouldBlock` error
+/// contains `FlockRetryError`.
+pub(crate) fn try_lock_retry<T, L>(
+	mut data: T,
+	mut policy: RetryPolicy,
+	try_lock: impl Fn(T) -> Result<L, FlockError<T>>,
+) -> Result<L, FlockError<T>>
+where
+	T: FlockElement,
+{
+	policy.start();
+	loop {
+		match try_lock(data) {
+			Ok(lock) => return Ok(lock),
+			Err(e) if e.is_would_block() => match policy.next_pause() {
+				Some(pause) => {
+					data = e.into_data();
+					std::thread::sleep(pause);
+				}
+				None => {
+					let (data, err) = e.into_all();
+					let stats = policy.stats();
+					let err = IoError::new(IoErrorKind::WouldBlock, FlockRetryError { stats, err });
+
+					return Err(FlockError::new(data, err));
+				}
+			},
+			Err(e) => return Err(e),
+		}
+	}
+}
@@ -0,0 +1,335 @@
+//! Locks of several files at once (Unix only).
+//!
+//! `FlockSet` locks the files in the order of (dev, inode), so processes locking
+//! overlapping sets never wait for each other in a cycle. If any lock fails,
+//! the locks already set are released and all files are returned in the error.
+//! The locks are released in reverse order.
+//!
+//! ```rust
+//! use cluFlock::mode::Exclusive;
+//! use cluFlock::set::FlockSet;
+//! use std::fs::File;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let db = File::create("./file")?;
+//! 	let journal = File::create("./test_file")?;
+//!
+//! 	let locks = FlockSet::try_lock_all([&db, &journal], Exclusive)?;
+//! 	println!("{:?}", locks);
+//!
+//! 	Ok(())
+//! } // auto unlock (journal, db or db, journal)
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::Shared;
+use crate::sys::RawFilePtr;
+use crate::ExclusiveFlock;
+use crate::FlockLock;
+use crate::SharedFlock;
+use core::fmt::Debug;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use std::vec::Vec;
+
+/// Lock mode of `FlockSet` (`Shared`, `Exclusive`).
+pub trait FlockSetMode: FlockLockMode + Sized {
+	/// Expect to get a lock of `data`.
+	fn wait_lock<T>(data: T) -> Result<FlockLock<T, Self>, FlockError<T>>
+	where
+		T: FlockElement<FilePtr = RawFilePtr>;
+
+	/// Get a lock of `data` without waiting.
+	fn try_lock<T>(data: T) -> Result<FlockLock<T, Self>, FlockError<T>>
+	where
+		T: FlockElement<FilePtr = RawFilePtr>;
+}
+
+impl FlockSetMode for Shared {
+	#[inline(always)]
+	fn wait_lock<T>(data: T) -> Result<FlockLock<T, Self>, FlockError<T>>
+	where
+		T: FlockElement<FilePtr = RawFilePtr>,
+	{
+		SharedFlock::wait_lock(data)
+	}
+
+	#[inline(always)]
+	fn try_lock<T>(data: T) -> Result<FlockLock<T, Self>, FlockError<T>>
+	where
+		T: FlockElement<FilePtr = RawFilePtr>,
+	{
+		SharedFlock::try_lock(data)
+	}
+}
+
+impl FlockSetMode for Exclusive {
+	#[inline(always)]
+	fn wait_lock<T>(data: T) -> Result<FlockLock<T, Self>, FlockError<T>>
+	where
+		T: FlockElement<FilePtr = RawFilePtr>,
+	{
+		ExclusiveFlock::wait_lock(data)
+	}
+
+	#[inline(always)]
+	fn try_lock<T>(data: T) -> Result<FlockLock<T, Self>, FlockError<T>>
+	where
+		T: FlockElement<FilePtr = RawFilePtr>,
+	{
+		ExclusiveFlock::try_lock(data)
+	}
+}
+
+/// Error of `FlockSet`, contains all files in the original order.
+pub struct FlockSetError<T> {
+	data: Vec<T>,
+	err: IoError,
+}
+
+impl<T> FlockSetError<T> {
+	#[inline]
+	fn from_options(data: Vec<Option<T>>, err: IoError) -> Self {
+		Self {
+			data: data.into_iter().flatten().collect(),
+			err,
+		}
+	}
+
+	/// The operation must be blocked to complete,
+	/// but it was requested that the blocking operation not be performed.
+	#[inline(always)]
+	pub fn is_would_block(&self) -> bool {
+		self.err.kind() == IoErrorKind::WouldBlock
+	}
+
+	/// Get a link to data.
+	#[inline(always)]
+	pub fn as_data(&self) -> &[T] {
+		&self.data
+	}
+
+	/// Get a link to err.
+	#[inline(always)]
+	pub const fn as_err(&self) -> &IoError {
+		&self.err
+	}
+
+	/// Retrieve only the data from the error structure.
+	#[inline(always)]
+	pub fn into_data(self) -> Vec<T> {
+		self.data
+	}
+
+	/// Get all data from the error structure.
+	#[inline(always)]
+	pub fn into_all(self) -> (Vec<T>, IoError) {
+		(self.data, self.err)
+	}
+
+	/// Get only the error from the error structure.
+	#[inline(always)]
+	pub fn into_err(self) -> IoError {
+		self.err
+	}
+}
+
+impl<T> Debug for FlockSetError<T> {
+	#[inline(always)]
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		Debug::fmt(&self.err, f)
+	}
+}
+
+impl<T> Display for FlockSetError<T> {
+	#[inline(always)]
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		Display::fmt(&self.err, f)
+	}
+}
+
+impl<T> std::error::Error for FlockSetError<T> {
+	#[inline(always)]
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		std::error::Error::source(&self.err)
+	}
+}
+
+impl<T> From<FlockSetError<T>> for IoError {
+	#[inline(always)]
+	fn from(a: FlockSetError<T>) -> IoError {
+		a.into_err()
+	}
+}
+
+/// Locks of several files, released in reverse order of acquisition.
+pub struct FlockSet<T, M = Exclusive>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	M: FlockSetMode,
+{
+	/// In the original order.
+	locks: Vec<Option<FlockLock<T, M>>>,
+	/// Indexes of `locks` in the order of acquisition.
+	order: Vec<usize>,
+}
+
+impl<T, M> FlockSet<T, M>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	M: FlockSetMode,
+{
+	/// Expect to get locks of all files (in the order of (dev, inode)).
+	#[inline]
+	pub fn lock_all(
+		files: impl IntoIterator<Item = T>,
+		_mode: M,
+	) -> Result<Self, FlockSetError<T>> {
+		Self::lock_all_with(files, M::wait_lock)
+	}
+
+	/// Get locks of all files without waiting or none of them
+	/// (`WouldBlock` if any of them is locked).
+	#[inline]
+	pub fn try_lock_all(
+		files: impl IntoIterator<Item = T>,
+		_mode: M,
+	) -> Result<Self, FlockSetError<T>> {
+		Self::lock_all_with(files, M::try_lock)
+	}
+
+	fn lock_all_with(
+		files: impl IntoIterator<Item = T>,
+		lock: impl Fn(T) -> Result<FlockLock<T, M>, FlockError<T>>,
+	) -> Result<Self, FlockSetError<T>> {
+		let files: Vec<T> = files.into_iter().collect();
+
+		let mut keys = Vec::with_capacity(files.len());
+		for (i, a) in files.iter().enumerate() {
+			match file_key(a.as_file_ptr()) {
+				Ok(key) => keys.push((key, i)),
+				Err(err) => return Err(FlockSetError { data: files, err }),
+			}
+		}
+		keys.sort_unstable();
+		if keys.windows(2).any(|a| a[0].0 == a[1].0) {
+			let err = IoError::new(
+				IoErrorKind::InvalidInput,
+				"the same file twice in a lock set",
+			);
+			return Err(FlockSetError { data: files, err });
+		}
+
+		let mut data: Vec<Option<T>> = files.into_iter().map(Some).collect();
+		let mut set = Self {
+			locks: data.iter().map(|_| None).collect(),
+			order: Vec::with_capacity(keys.len()),
+		};
+		for (_key, i) in keys {
+			let Some(a) = data[i].take() else { continue };
+			match lock(a) {
+				Ok(lock) => {
+					set.locks[i] = Some(lock);
+					set.order.push(i);
+				}
+				Err(e) => {
+					let (a, err) = e.into_all();
+					data[i] = Some(a);
+
+					// All or nothing.
+					for (i, a) in set.release() {
+						data[i] = Some(a);
+					}
+					return Err(FlockSetError::from_options(data, err));
+				}
+			}
+		}
+
+		Ok(set)
+	}
+
+	/// Release the locks in reverse order, get the files with their indexes.
+	fn release(&mut self) -> Vec<(usize, T)> {
+		let mut data = Vec::with_capacity(self.order.len());
+		while let Some(i) = self.order.pop() {
+			if let Some(lock) = self.locks[i].take() {
+				data.push((i, lock.unlock_data_no_err_result()));
+			}
+		}
+
+		data
+	}
+
+	/// Number of locks.
+	#[inline(always)]
+	pub fn len(&self) -> usize {
+		self.locks.len()
+	}
+
+	/// The set is empty.
+	#[inline(always)]
+	pub fn is_empty(&self) -> bool {
+		self.locks.is_empty()
+	}
+
+	/// Get a link to the lock of the file (in the original order).
+	#[inline]
+	pub fn get(&self, i: usize) -> Option<&FlockLock<T, M>> {
+		self.locks.get(i).and_then(Option::as_ref)
+	}
+
+	/// Get the locks (in the original order).
+	#[inline]
+	pub fn iter(&self) -> impl Iterator<Item = &FlockLock<T, M>> {
+		self.locks.iter().flatten()
+	}
+
+	/// Release the locks in reverse order and get the files (in the original order).
+	pub fn unlock_data(mut self) -> Vec<T> {
+		let mut data: Vec<Option<T>> = self.locks.iter().map(|_| None).collect();
+		for (i, a) in self.release() {
+			data[i] = Some(a);
+		}
+
+		data.into_iter().flatten().collect()
+	}
+}
+
+impl<T, M> Debug for FlockSet<T, M>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	M: FlockSetMode,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("FlockSet")
+			.field("locks", &self.locks)
+			.field("order", &self.order)
+			.finish()
+	}
+}
+
+impl<T, M> Drop for FlockSet<T, M>
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+	M: FlockSetMode,
+{
+	#[inline]
+	fn drop(&mut self) {
+		self.release();
+	}
+}
+
+/// Identity of the file (dev, inode).
+fn file_key(fd: RawFilePtr) -> Result<(u64, u64), IoError> {
+	let mut stat = unsafe { core::mem::zeroed::<libc::stat>() };
+
+	match unsafe { libc::fstat(fd, &mut stat) } {
+		0 => Ok((stat.st_dev as u64, stat.st_ino as u64)),
+		_ => Err(IoError::last_os_error()),
+	}
+}
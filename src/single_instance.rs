@@ -0,0 +1,256 @@
+//! Single instance of an application per user (Unix only).
+//!
+//! The first instance gets an exclusive lock of `<name>.lock` in `$XDG_RUNTIME_DIR`
+//! (or in `/tmp/cluFlock-<uid>`, a directory accessible only to the user) and
+//! listens on the Unix socket `<name>.sock` next to it. The other instances can
+//! send it a message (for example, their command line arguments).
+//!
+//! ```rust
+//! use cluFlock::single_instance::SingleInstance;
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	match SingleInstance::acquire("cluFlock-doc-app")? {
+//! 		SingleInstance::Primary(primary) => {
+//! 			let receiver = primary.receiver()?;
+//! 			std::thread::spawn(move || {
+//! 				for message in receiver {
+//! 					println!("{:?}", message);
+//! 				}
+//! 			});
+//! 		}
+//! 		SingleInstance::Secondary(secondary) => {
+//! 			let args = std::env::args().collect::<Vec<_>>().join("\n");
+//! 			secondary.send(args.as_bytes())?;
+//! 		}
+//! 	}
+//!
+//! 	Ok(())
+//! }
+//! ```
+
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::ExclusiveFlock;
+use crate::FlockLock;
+use std::fs::DirBuilder;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Write;
+use std::net::Shutdown;
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::net::UnixListener;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use std::vec::Vec;
+
+/// How long a secondary instance waits for the primary to start listening.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const CONNECT_PAUSE: Duration = Duration::from_millis(10);
+/// How long the primary instance waits for data of a connected secondary.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Instance of the application.
+#[derive(Debug)]
+pub enum SingleInstance {
+	/// The first instance, holds the lock and receives messages.
+	Primary(PrimaryInstance),
+	/// The application is already running, messages can be sent to it.
+	Secondary(SecondaryInstance),
+}
+
+impl SingleInstance {
+	/// Get the lock of the application `name` in the runtime directory of the user.
+	pub fn acquire(name: &str) -> Result<Self, IoError> {
+		Self::acquire_in(runtime_dir()?, name)
+	}
+
+	/// Get the lock of the application `name` in `dir`.
+	pub fn acquire_in(dir: impl AsRef<Path>, name: &str) -> Result<Self, IoError> {
+		if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+			return Err(IoError::new(
+				IoErrorKind::InvalidInput,
+				"invalid name of the application",
+			));
+		}
+		let dir = dir.as_ref();
+		let lock_path = dir.join(format!("{}.lock", name));
+		let socket_path = dir.join(format!("{}.sock", name));
+
+		let file = OpenOptions::new()
+			.read(true)
+			.write(true)
+			.create(true)
+			.truncate(false)
+			.mode(0o600)
+			.open(&lock_path)?;
+		let lock = match ExclusiveFlock::try_lock(file) {
+			Ok(lock) => lock,
+			Err(e) if e.is_would_block() => {
+				return Ok(Self::Secondary(SecondaryInstance { socket_path }));
+			}
+			Err(e) => return Err(e.into_err()),
+		};
+
+		// Only the lock holder gets here, the socket is left by a previous primary.
+		match std::fs::remove_file(&socket_path) {
+			Ok(()) => {}
+			Err(e) if e.kind() == IoErrorKind::NotFound => {}
+			Err(e) => return Err(e),
+		}
+		let listener = UnixListener::bind(&socket_path)?;
+
+		Ok(Self::Primary(PrimaryInstance {
+			listener,
+			socket_path,
+			lock,
+		}))
+	}
+
+	/// Is this the first instance.
+	#[inline(always)]
+	pub const fn is_primary(&self) -> bool {
+		matches!(self, Self::Primary(..))
+	}
+}
+
+/// The first instance of the application, the lock is released when it is dropped.
+#[derive(Debug)]
+pub struct PrimaryInstance {
+	listener: UnixListener,
+	socket_path: PathBuf,
+	lock: FlockLock<File>,
+}
+
+impl PrimaryInstance {
+	/// Get a receiver of messages from the secondary instances.
+	#[inline]
+	pub fn receiver(&self) -> Result<InstanceReceiver, IoError> {
+		Ok(InstanceReceiver {
+			listener: self.listener.try_clone()?,
+		})
+	}
+
+	/// Path of the socket.
+	#[inline(always)]
+	pub fn as_socket_path(&self) -> &Path {
+		&self.socket_path
+	}
+
+	/// Get a link to the lock.
+	#[inline(always)]
+	pub const fn as_lock(&self) -> &FlockLock<File> {
+		&self.lock
+	}
+}
+
+impl Drop for PrimaryInstance {
+	#[inline]
+	fn drop(&mut self) {
+		// Still locked: the next primary will bind its own socket.
+		let _e = std::fs::remove_file(&self.socket_path);
+	}
+}
+
+/// Receiver of messages from the secondary instances.
+///
+/// Is an endless iterator of messages.
+#[derive(Debug)]
+pub struct InstanceReceiver {
+	listener: UnixListener,
+}
+
+impl InstanceReceiver {
+	/// Wait for the next message.
+	///
+	/// A secondary that stops sending is dropped by timeout (`WouldBlock`
+	/// or `TimedOut` error), the next message can be received.
+	pub fn recv(&self) -> Result<Vec<u8>, IoError> {
+		let (mut stream, _addr) = self.listener.accept()?;
+		stream.set_read_timeout(Some(READ_TIMEOUT))?;
+		let mut message = Vec::new();
+		stream.read_to_end(&mut message)?;
+
+		Ok(message)
+	}
+}
+
+impl Iterator for InstanceReceiver {
+	type Item = Result<Vec<u8>, IoError>;
+
+	#[inline]
+	fn next(&mut self) -> Option<Self::Item> {
+		Some(self.recv())
+	}
+}
+
+/// The application is already running.
+#[derive(Debug)]
+pub struct SecondaryInstance {
+	socket_path: PathBuf,
+}
+
+impl SecondaryInstance {
+	/// Send a message to the primary instance.
+	///
+	/// Waits a little if the primary has not started listening yet.
+	pub fn send(&self, message: &[u8]) -> Result<(), IoError> {
+		let start = Instant::now();
+		let mut stream = loop {
+			match UnixStream::connect(&self.socket_path) {
+				Ok(stream) => break stream,
+				Err(e)
+					if matches!(
+						e.kind(),
+						IoErrorKind::NotFound | IoErrorKind::ConnectionRefused
+					) && start.elapsed() < CONNECT_TIMEOUT =>
+				{
+					std::thread::sleep(CONNECT_PAUSE);
+				}
+				Err(e) => return Err(e),
+			}
+		};
+		stream.write_all(message)?;
+		stream.shutdown(Shutdown::Write)
+	}
+
+	/// Path of the socket.
+	#[inline(always)]
+	pub fn as_socket_path(&self) -> &Path {
+		&self.socket_path
+	}
+}
+
+/// `$XDG_RUNTIME_DIR`, or `/tmp/cluFlock-<uid>` created with `0700`.
+fn runtime_dir() -> Result<PathBuf, IoError> {
+	if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+		let dir = PathBuf::from(dir);
+		if dir.is_absolute() {
+			return Ok(dir);
+		}
+	}
+
+	let uid = unsafe { libc::getuid() };
+	let dir = PathBuf::from(format!("/tmp/cluFlock-{}", uid));
+	match DirBuilder::new().mode(0o700).create(&dir) {
+		Ok(()) => {}
+		Err(e) if e.kind() == IoErrorKind::AlreadyExists => {}
+		Err(e) => return Err(e),
+	}
+
+	// The directory could have been created by another user.
+	let meta = std::fs::symlink_metadata(&dir)?;
+	if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+		return Err(IoError::new(
+			IoErrorKind::PermissionDenied,
+			"the runtime directory is not owned by the user or is accessible to others",
+		));
+	}
+
+	Ok(dir)
+}
@@ -0,0 +1,184 @@
+//! Small state file shared between processes (Unix only).
+//!
+//! `LockedState` reads the state under a shared lock and updates it under an
+//! exclusive lock (read, change, write). The new state is written to a
+//! temporary file and renamed over the state file, so a crashed writer leaves
+//! the old state. The lock is held on a separate `path.lock` file, whose inode
+//! is not replaced by the rename. The format is defined by `StateCodec`.
+//!
+//! ```rust
+//! use cluFlock::state::LockedState;
+//! use cluFlock::state::StateCodec;
+//! use std::io::Error;
+//! use std::io::ErrorKind;
+//!
+//! struct Counter;
+//!
+//! impl StateCodec<u64> for Counter {
+//! 	fn encode(&self, state: &u64, out: &mut Vec<u8>) -> Result<(), Error> {
+//! 		out.extend_from_slice(state.to_string().as_bytes());
+//! 		Ok(())
+//! 	}
+//!
+//! 	fn decode(&self, data: &[u8]) -> Result<u64, Error> {
+//! 		std::str::from_utf8(data)
+//! 			.ok()
+//! 			.and_then(|a| a.parse().ok())
+//! 			.ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid counter"))
+//! 	}
+//! }
+//!
+//! fn main() -> Result<(), std::io::Error> {
+//! 	let state = LockedState::new(std::env::temp_dir().join("cluFlock_doc_state"), Counter);
+//! 	let count = state.update(|count| {
+//! 		*count += 1;
+//! 		*count
+//! 	})?;
+//! 	assert_eq!(state.get()?, count);
+//!
+//! 	std::fs::remove_file(state.as_path())?;
+//! 	std::fs::remove_file(state.as_lock_path())
+//! }
+//! ```
+
+use crate::err::IoError;
+use crate::err::IoErrorKind;
+use crate::lock_file::sync_parent_dir;
+use crate::path_lock::PathLock;
+use core::marker::PhantomData;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::vec::Vec;
+
+/// Format of the state file.
+pub trait StateCodec<T> {
+	/// Write the state to `out`.
+	fn encode(&self, state: &T, out: &mut Vec<u8>) -> Result<(), IoError>;
+
+	/// Read the state from the content of the file (`InvalidData` if it is damaged).
+	fn decode(&self, data: &[u8]) -> Result<T, IoError>;
+}
+
+/// State file shared between processes, a missing file is the default state.
+#[derive(Debug)]
+pub struct LockedState<T, C>
+where
+	T: Default,
+	C: StateCodec<T>,
+{
+	path: PathBuf,
+	lock_path: PathBuf,
+	tmp_path: PathBuf,
+	codec: C,
+	_state: PhantomData<fn() -> T>,
+}
+
+impl<T, C> LockedState<T, C>
+where
+	T: Default,
+	C: StateCodec<T>,
+{
+	/// State at the path (nothing is opened yet).
+	pub fn new(path: impl AsRef<Path>, codec: C) -> Self {
+		let path = path.as_ref().to_path_buf();
+		let with_ext = |ext: &str| {
+			let mut a = OsString::from(path.as_os_str());
+			a.push(ext);
+
+			PathBuf::from(a)
+		};
+
+		Self {
+			lock_path: with_ext(".lock"),
+			tmp_path: with_ext(".tmp"),
+			path,
+			codec,
+			_state: PhantomData,
+		}
+	}
+
+	/// Path of the state file.
+	#[inline(always)]
+	pub fn as_path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Path of the lock file.
+	#[inline(always)]
+	pub fn as_lock_path(&self) -> &Path {
+		&self.lock_path
+	}
+
+	/// Get a link to the codec.
+	#[inline(always)]
+	pub const fn as_codec(&self) -> &C {
+		&self.codec
+	}
+
+	/// Read the state under a shared lock (nothing is created).
+	pub fn get(&self) -> Result<T, IoError> {
+		// Read-only, the directory may be read-only for this process.
+		let lock = match PathLock::wait_shared_lock_existing(&self.lock_path) {
+			Ok(lock) => Some(lock),
+			// There were no updates yet, the state file is replaced only by rename.
+			Err(e) if e.kind() == IoErrorKind::NotFound => None,
+			Err(e) => return Err(e),
+		};
+		let state = self.read()?;
+		if let Some(lock) = lock {
+			lock.unlock()?;
+		}
+
+		Ok(state)
+	}
+
+	/// Change the state under an exclusive lock, the new state is written
+	/// only if `f` succeeds.
+	pub fn try_update<R, E>(&self, f: impl FnOnce(&mut T) -> Result<R, E>) -> Result<R, E>
+	where
+		E: From<IoError>,
+	{
+		let lock = PathLock::wait_exclusive_lock(&self.lock_path)?;
+		let mut state = self.read()?;
+		let result = f(&mut state)?;
+		self.write(&state)?;
+		lock.unlock()?;
+
+		Ok(result)
+	}
+
+	/// Change the state under an exclusive lock and write it.
+	#[inline]
+	pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, IoError> {
+		self.try_update(|state| Ok(f(state)))
+	}
+
+	fn read(&self) -> Result<T, IoError> {
+		match std::fs::read(&self.path) {
+			Ok(data) => self.codec.decode(&data),
+			Err(e) if e.kind() == IoErrorKind::NotFound => Ok(T::default()),
+			Err(e) => Err(e),
+		}
+	}
+
+	/// Write to the temporary file and rename it over the state file.
+	fn write(&self, state: &T) -> Result<(), IoError> {
+		let mut data = Vec::new();
+		self.codec.encode(state, &mut data)?;
+
+		// Only the holder of the exclusive lock uses the temporary file.
+		let result = File::create(&self.tmp_path).and_then(|mut file| {
+			file.write_all(&data)?;
+			file.sync_all()
+		});
+		if let Err(e) = result.and_then(|_| std::fs::rename(&self.tmp_path, &self.path)) {
+			let _e = std::fs::remove_file(&self.tmp_path);
+			return Err(e);
+		}
+
+		sync_parent_dir(&self.path)
+	}
+}
@@ -3,6 +3,9 @@
 use crate::element::FlockElement;
 use crate::err::FlockError;
 use crate::err::IoError;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::Shared;
 use crate::range::checker::FlockRangePNumBehChecker;
 use crate::range::pnum::FlockRangePNumBeh;
 use crate::range::FlockRange;
@@ -10,8 +13,11 @@ use crate::range::FlockRangeFull;
 use crate::unlock::WaitFlockUnlock;
 use crate::unlock::WaitFlockUnlockRange;
 use crate::ExclusiveFlock;
+use crate::ExclusiveFlockRange;
 use crate::FlockLock;
+use crate::FlockRangeLock;
 use crate::SharedFlock;
+use crate::SharedFlockRange;
 use winapi::shared::minwindef::DWORD;
 use winapi::um::minwinbase::LPOVERLAPPED;
 use winapi::um::minwinbase::OVERLAPPED;
@@ -179,10 +185,10 @@ where
 	#[inline]
 	fn try_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R {
-		next_safe_flock::<FlockMethod, _, _, _, _, _, _, _, _>(
+		next_safe_flock::<FlockMethod, _, _, _, _, _, _, _, _, _>(
 			self,
 			__internal_flags::TRY_SHARED_LOCK,
 			FlockRangeFull::<DWORD>::full(),
@@ -194,10 +200,10 @@ where
 	#[inline]
 	fn wait_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R {
-		next_safe_flock::<FlockMethod, _, _, _, _, _, _, _, _>(
+		next_safe_flock::<FlockMethod, _, _, _, _, _, _, _, _, _>(
 			self,
 			__internal_flags::WAIT_SHARED_LOCK,
 			FlockRangeFull::<DWORD>::full(),
@@ -214,10 +220,10 @@ where
 	#[inline]
 	fn try_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R {
-		next_safe_flock::<FlockMethod, _, _, _, _, _, _, _, _>(
+		next_safe_flock::<FlockMethod, _, _, _, _, _, _, _, _, _>(
 			self,
 			__internal_flags::TRY_EXCLUSIVE_LOCK,
 			FlockRangeFull::<DWORD>::full(),
@@ -229,10 +235,10 @@ where
 	#[inline]
 	fn wait_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R {
-		next_safe_flock::<FlockMethod, _, _, _, _, _, _, _, _>(
+		next_safe_flock::<FlockMethod, _, _, _, _, _, _, _, _, _>(
 			self,
 			__internal_flags::WAIT_EXCLUSIVE_LOCK,
 			FlockRangeFull::<DWORD>::full(),
@@ -242,6 +248,100 @@ where
 	}
 }
 
+impl<T> SharedFlockRange for T
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+{
+	#[inline]
+	fn try_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		next_safe_range_flock::<FlockMethod, _, _, _, _, _, _, _, _, _>(
+			self,
+			__internal_flags::TRY_SHARED_LOCK,
+			range,
+			next,
+			errf,
+		)
+	}
+
+	#[inline]
+	fn wait_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		next_safe_range_flock::<FlockMethod, _, _, _, _, _, _, _, _, _>(
+			self,
+			__internal_flags::WAIT_SHARED_LOCK,
+			range,
+			next,
+			errf,
+		)
+	}
+}
+
+impl<T> ExclusiveFlockRange for T
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+{
+	#[inline]
+	fn try_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		next_safe_range_flock::<FlockMethod, _, _, _, _, _, _, _, _, _>(
+			self,
+			__internal_flags::TRY_EXCLUSIVE_LOCK,
+			range,
+			next,
+			errf,
+		)
+	}
+
+	#[inline]
+	fn wait_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		next_safe_range_flock::<FlockMethod, _, _, _, _, _, _, _, _, _>(
+			self,
+			__internal_flags::WAIT_EXCLUSIVE_LOCK,
+			range,
+			next,
+			errf,
+		)
+	}
+}
+
 enum FlockMethod {} // default
 enum UnflockMethod {}
 
@@ -453,7 +553,8 @@ fn next_safe_flock<
 	NE: FlockRangePNumBeh + PartialOrd<NS>,
 	C: FlockRangePNumBehChecker,
 	IRange: Into<FlockRange<NS, NE, C>>,
-	N: FnOnce(FlockLock<FE>) -> R,
+	M: FlockLockMode,
+	N: FnOnce(FlockLock<FE, M>) -> R,
 	NF: FnOnce(FlockError<FE>) -> R,
 	R,
 >(
@@ -475,3 +576,42 @@ fn next_safe_flock<
 		errf,
 	)
 }
+
+#[inline(always)]
+fn next_safe_range_flock<
+	FLM: CurrentFlockMethod,
+	FE: FlockElement<FilePtr = RawFilePtr>,
+	NS: FlockRangePNumBeh,
+	NE: FlockRangePNumBeh + PartialOrd<NS>,
+	C: FlockRangePNumBehChecker,
+	IRange: Into<FlockRange<NS, NE, C>>,
+	M: FlockLockMode,
+	N: FnOnce(FlockRangeLock<FE, M>) -> R,
+	NF: FnOnce(FlockError<FE>) -> R,
+	R,
+>(
+	data: FE,
+	flag: FLM::InFlags,
+	range: IRange,
+	next: N,
+	errf: NF,
+) -> R {
+	let range = range.into();
+	let prange = range.into_primitive();
+
+	next_force_flock::<FLM, _, _, _, _, _, _, _, _>(
+		data,
+		flag,
+		range,
+		|data| {
+			let safe_flock = unsafe {
+				let range = prange.into_ignore_prangechecker();
+
+				FlockRangeLock::from_flock(FlockLock::force_new(data), range)
+			};
+
+			next(safe_flock)
+		},
+		errf,
+	)
+}
@@ -1,15 +1,28 @@
 //! Implementation for platforms with flock support.
 
+use crate::conflict::FlockConflict;
 use crate::element::FlockElement;
 use crate::err::FlockError;
 use crate::err::IoError;
-use crate::range::pnum::FlockRangePNumBeh;
+use crate::interrupt::FlockInterruptPolicy;
+use crate::interrupt::RetryInterrupted;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::FlockMode;
+use crate::mode::Shared;
+use crate::range::checker::FlockRangePNumBehChecker;
 use crate::range::pnum::__make_auto_pnum_type;
+use crate::range::pnum::FlockRangePNumBeh;
+use crate::range::FlockRange;
 use crate::unlock::TryFlockUnlock;
 use crate::unlock::WaitFlockUnlock;
+use crate::unlock::WaitFlockUnlockRange;
 use crate::ExclusiveFlock;
+use crate::ExclusiveFlockRange;
 use crate::FlockLock;
+use crate::FlockRangeLock;
 use crate::SharedFlock;
+use crate::SharedFlockRange;
 
 crate::cfg_std! {
 	if #std {
@@ -33,11 +46,13 @@ crate::cfg_std! {
 	}
 }
 
-pub type FlockRangePNum = usize; // unsupport :(
+/// Platform number specifying platform numbers for locks.
+/// (Ranges are set by POSIX `fcntl` locks, `off_t`.)
+pub type FlockRangePNum = libc::off_t;
 
 impl FlockRangePNumBeh for FlockRangePNum {
-	const MIN: usize = 1;
-	const MAX: usize = 0;
+	const MIN: FlockRangePNum = 0;
+	const MAX: FlockRangePNum = FlockRangePNum::MAX;
 
 	#[inline(always)]
 	fn get_pnum(self) -> FlockRangePNum {
@@ -46,6 +61,17 @@ impl FlockRangePNumBeh for FlockRangePNum {
 }
 __make_auto_pnum_type!(FlockRangePNum);
 
+impl FlockRangePNumBeh for usize {
+	const MIN: usize = 0 as _;
+	const MAX: usize = FlockRangePNum::MAX as _;
+
+	#[inline(always)]
+	fn get_pnum(self) -> FlockRangePNum {
+		self as _
+	}
+}
+__make_auto_pnum_type!(usize);
+
 mod __internal_flags {
 	pub type LibcFlag = libc::c_int;
 
@@ -57,6 +83,25 @@ mod __internal_flags {
 
 	pub const TRY_UNLOCK: LibcFlag = WAIT_UNLOCK | libc::LOCK_NB;
 	pub const WAIT_UNLOCK: LibcFlag = libc::LOCK_UN;
+
+	// POSIX fcntl (range)
+	pub const TRY_RANGE_LOCK: LibcFlag = libc::F_SETLK;
+	pub const WAIT_RANGE_LOCK: LibcFlag = libc::F_SETLKW;
+	pub const RANGE_UNLOCK: LibcFlag = libc::F_SETLK;
+
+	// OFD fcntl (Linux)
+	#[cfg(target_os = "linux")]
+	pub const TRY_OFD_LOCK: LibcFlag = libc::F_OFD_SETLK;
+	#[cfg(target_os = "linux")]
+	pub const WAIT_OFD_LOCK: LibcFlag = libc::F_OFD_SETLKW;
+	#[cfg(target_os = "linux")]
+	pub const OFD_UNLOCK: LibcFlag = libc::F_OFD_SETLK;
+	#[cfg(target_os = "linux")]
+	pub const OFD_GETLK: LibcFlag = libc::F_OFD_GETLK;
+
+	pub const EXCLUSIVE_RANGE: LibcFlag = libc::F_WRLCK as _;
+	pub const SHARED_RANGE: LibcFlag = libc::F_RDLCK as _;
+	pub const UNLOCK_RANGE: LibcFlag = libc::F_UNLCK as _;
 }
 
 impl<T> TryFlockUnlock for T
@@ -124,7 +169,7 @@ where
 	#[inline]
 	fn try_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R {
 		next_safe_flock(self, __internal_flags::TRY_SHARED_LOCK, next, errf)
@@ -133,10 +178,26 @@ where
 	#[inline]
 	fn wait_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R {
-		next_safe_flock(self, __internal_flags::WAIT_SHARED_LOCK, next, errf)
+		SharedFlock::wait_lock_interruptible_fn(self, RetryInterrupted, next, errf)
+	}
+
+	#[inline]
+	fn wait_lock_interruptible_fn<R>(
+		self,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		let flag = __internal_flags::WAIT_SHARED_LOCK;
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			next_safe_flock(data, flag, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
 	}
 }
 
@@ -147,7 +208,7 @@ where
 	#[inline]
 	fn try_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R {
 		next_safe_flock(self, __internal_flags::TRY_EXCLUSIVE_LOCK, next, errf)
@@ -156,10 +217,237 @@ where
 	#[inline]
 	fn wait_lock_fn<R>(
 		self,
-		next: impl FnOnce(FlockLock<Self>) -> R,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
 		errf: impl FnOnce(FlockError<Self>) -> R,
 	) -> R {
-		next_safe_flock(self, __internal_flags::WAIT_EXCLUSIVE_LOCK, next, errf)
+		ExclusiveFlock::wait_lock_interruptible_fn(self, RetryInterrupted, next, errf)
+	}
+
+	#[inline]
+	fn wait_lock_interruptible_fn<R>(
+		self,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R {
+		let flag = __internal_flags::WAIT_EXCLUSIVE_LOCK;
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			next_safe_flock(data, flag, Ok, Err)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
+}
+
+impl<T> WaitFlockUnlockRange for T
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+{
+	#[inline]
+	unsafe fn unlock_range_no_result<NS, NE, C>(&mut self, range: impl Into<FlockRange<NS, NE, C>>)
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		next_force_fcntl(
+			self,
+			__internal_flags::RANGE_UNLOCK,
+			__internal_flags::UNLOCK_RANGE,
+			range,
+			|_, _| (),
+			|_| (),
+		)
+	}
+
+	#[inline]
+	unsafe fn unlock_range<NS, NE, C>(
+		&mut self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+	) -> Result<(), IoError>
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		WaitFlockUnlockRange::unlock_range_fn(self, range, || Ok(()), Err)
+	}
+
+	#[inline]
+	unsafe fn unlock_range_fn<R, NS, NE, C>(
+		&mut self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce() -> R,
+		errf: impl FnOnce(IoError) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		next_force_fcntl(
+			self,
+			__internal_flags::RANGE_UNLOCK,
+			__internal_flags::UNLOCK_RANGE,
+			range,
+			|_, _| next(),
+			|e| errf(e.into_err()),
+		)
+	}
+}
+
+impl<T> SharedFlockRange for T
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+{
+	#[inline]
+	fn try_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		next_safe_fcntl(
+			self,
+			__internal_flags::TRY_RANGE_LOCK,
+			__internal_flags::SHARED_RANGE,
+			range,
+			next,
+			errf,
+		)
+	}
+
+	#[inline]
+	fn wait_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		SharedFlockRange::wait_lock_range_interruptible_fn(
+			self,
+			range,
+			RetryInterrupted,
+			next,
+			errf,
+		)
+	}
+
+	#[inline]
+	fn wait_lock_range_interruptible_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockRangeLock<Self, Shared>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		let range = range.into();
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			next_safe_fcntl(
+				data,
+				__internal_flags::WAIT_RANGE_LOCK,
+				__internal_flags::SHARED_RANGE,
+				range,
+				Ok,
+				Err,
+			)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
+	}
+}
+
+impl<T> ExclusiveFlockRange for T
+where
+	T: FlockElement<FilePtr = RawFilePtr>,
+{
+	#[inline]
+	fn try_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		next_safe_fcntl(
+			self,
+			__internal_flags::TRY_RANGE_LOCK,
+			__internal_flags::EXCLUSIVE_RANGE,
+			range,
+			next,
+			errf,
+		)
+	}
+
+	#[inline]
+	fn wait_lock_range_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		ExclusiveFlockRange::wait_lock_range_interruptible_fn(
+			self,
+			range,
+			RetryInterrupted,
+			next,
+			errf,
+		)
+	}
+
+	#[inline]
+	fn wait_lock_range_interruptible_fn<R, NS, NE, C>(
+		self,
+		range: impl Into<FlockRange<NS, NE, C>>,
+		policy: impl FlockInterruptPolicy,
+		next: impl FnOnce(FlockRangeLock<Self, Exclusive>) -> R,
+		errf: impl FnOnce(FlockError<Self>) -> R,
+	) -> R
+	where
+		NS: FlockRangePNumBeh,
+		NE: FlockRangePNumBeh + PartialOrd<NS>,
+		C: FlockRangePNumBehChecker,
+	{
+		let range = range.into();
+		match crate::interrupt::retry_interrupted(self, policy, |data| {
+			next_safe_fcntl(
+				data,
+				__internal_flags::WAIT_RANGE_LOCK,
+				__internal_flags::EXCLUSIVE_RANGE,
+				range,
+				Ok,
+				Err,
+			)
+		}) {
+			Ok(lock) => next(lock),
+			Err(e) => errf(e),
+		}
 	}
 }
 
@@ -200,10 +488,10 @@ fn next_force_flock<D: FlockElement<FilePtr = RawFilePtr>, R>(
 }
 
 #[inline(always)]
-fn next_safe_flock<D: FlockElement<FilePtr = RawFilePtr>, R>(
+fn next_safe_flock<D: FlockElement<FilePtr = RawFilePtr>, M: FlockLockMode, R>(
 	data: D,
 	flag: __internal_flags::LibcFlag,
-	next: impl FnOnce(FlockLock<D>) -> R,
+	next: impl FnOnce(FlockLock<D, M>) -> R,
 	errf: impl FnOnce(FlockError<D>) -> R,
 ) -> R {
 	next_force_flock(
@@ -217,3 +505,246 @@ fn next_safe_flock<D: FlockElement<FilePtr = RawFilePtr>, R>(
 		errf,
 	)
 }
+
+/// Make a `struct flock` describing the (A..=B) range.
+/// The range ending at the maximum is set as `l_len = 0`
+/// (until the end of the file, including its future growth).
+#[inline(always)]
+fn make_raw_flock(
+	l_type: __internal_flags::LibcFlag,
+	(start, end): (FlockRangePNum, FlockRangePNum),
+) -> libc::flock {
+	#[allow(unused_unsafe)]
+	let mut raw_flock: libc::flock = unsafe { core::mem::zeroed() };
+
+	raw_flock.l_type = l_type as _;
+	raw_flock.l_whence = libc::SEEK_SET as _;
+	raw_flock.l_start = start;
+	raw_flock.l_len = match end == <FlockRangePNum as FlockRangePNumBeh>::MAX {
+		true => 0,
+		false => end - start + 1,
+	};
+
+	raw_flock
+}
+
+/// Get the last `fcntl` error, `EACCES` (allowed by POSIX for an already
+/// locked range) is always reduced to `EAGAIN`.
+#[inline]
+fn fcntl_last_os_error() -> IoError {
+	let platform_err = IoError::last_os_error();
+
+	#[cfg(feature = "std")]
+	if platform_err.raw_os_error() == Some(libc::EACCES) {
+		return IoError::from_raw_os_error(libc::EAGAIN);
+	}
+
+	platform_err
+}
+
+#[inline(always)]
+fn next_force_fcntl<D, NS, NE, C, R>(
+	data: D,
+	cmd: __internal_flags::LibcFlag,
+	l_type: __internal_flags::LibcFlag,
+	range: impl Into<FlockRange<NS, NE, C>>,
+	next: impl FnOnce(D, (FlockRangePNum, FlockRangePNum)) -> R,
+	errf: impl FnOnce(FlockError<D>) -> R,
+) -> R
+where
+	D: FlockElement,
+	D::FilePtr: Into<RawFilePtr>,
+	NS: FlockRangePNumBeh,
+	NE: FlockRangePNumBeh + PartialOrd<NS>,
+	C: FlockRangePNumBehChecker,
+{
+	let range = range.into();
+
+	match range.check_range_and_get() {
+		Ok(prange) => {
+			let ptr = FlockElement::as_file_ptr(&data).into();
+
+			match raw_fcntl(ptr, cmd, l_type, prange) {
+				Ok(()) => next(data, prange),
+				Err(platform_err) => errf(FlockError::new(data, platform_err)),
+			}
+		}
+		Err(err_range) => errf(FlockError::new(data, err_range.make_io_error())),
+	}
+}
+
+#[inline(always)]
+fn next_safe_fcntl<D, M, NS, NE, C, R>(
+	data: D,
+	cmd: __internal_flags::LibcFlag,
+	l_type: __internal_flags::LibcFlag,
+	range: impl Into<FlockRange<NS, NE, C>>,
+	next: impl FnOnce(FlockRangeLock<D, M>) -> R,
+	errf: impl FnOnce(FlockError<D>) -> R,
+) -> R
+where
+	D: FlockElement + WaitFlockUnlock + WaitFlockUnlockRange,
+	M: FlockLockMode,
+	D::FilePtr: Into<RawFilePtr>,
+	NS: FlockRangePNumBeh,
+	NE: FlockRangePNumBeh + PartialOrd<NS>,
+	C: FlockRangePNumBehChecker,
+{
+	next_force_fcntl(
+		data,
+		cmd,
+		l_type,
+		range,
+		|data, (start, end)| {
+			let safe_flock = unsafe {
+				let range = FlockRange::new_ignore_range_check(start, end);
+
+				FlockRangeLock::from_flock(FlockLock::force_new(data), range)
+			};
+
+			next(safe_flock)
+		},
+		errf,
+	)
+}
+
+/// Get `flock` flags for the lock (Some(mode)) or unlock (None).
+#[inline(always)]
+const fn flock_flag(mode: Option<FlockMode>, is_wait: bool) -> __internal_flags::LibcFlag {
+	match (mode, is_wait) {
+		(Some(FlockMode::Exclusive), true) => __internal_flags::WAIT_EXCLUSIVE_LOCK,
+		(Some(FlockMode::Exclusive), false) => __internal_flags::TRY_EXCLUSIVE_LOCK,
+		(Some(FlockMode::Shared), true) => __internal_flags::WAIT_SHARED_LOCK,
+		(Some(FlockMode::Shared), false) => __internal_flags::TRY_SHARED_LOCK,
+		(None, true) => __internal_flags::WAIT_UNLOCK,
+		(None, false) => __internal_flags::TRY_UNLOCK,
+	}
+}
+
+/// Get `l_type` of `struct flock` for the lock (Some(mode)) or unlock (None).
+#[inline(always)]
+const fn fcntl_l_type(mode: Option<FlockMode>) -> __internal_flags::LibcFlag {
+	match mode {
+		Some(FlockMode::Exclusive) => __internal_flags::EXCLUSIVE_RANGE,
+		Some(FlockMode::Shared) => __internal_flags::SHARED_RANGE,
+		None => __internal_flags::UNLOCK_RANGE,
+	}
+}
+
+#[inline(always)]
+fn raw_fcntl(
+	ptr: RawFilePtr,
+	cmd: __internal_flags::LibcFlag,
+	l_type: __internal_flags::LibcFlag,
+	prange: (FlockRangePNum, FlockRangePNum),
+) -> Result<(), IoError> {
+	let raw_flock = make_raw_flock(l_type, prange);
+
+	match unsafe { libc::fcntl(ptr, cmd, &raw_flock as *const libc::flock) } {
+		-1 => Err(fcntl_last_os_error()),
+		_ => Ok(()),
+	}
+}
+
+/// Lock (Some(mode)) or unlock (None) the whole file with `flock`.
+#[inline]
+pub fn backend_flock(
+	ptr: RawFilePtr,
+	mode: Option<FlockMode>,
+	is_wait: bool,
+) -> Result<(), IoError> {
+	match unsafe { libc::flock(ptr, flock_flag(mode, is_wait)) } {
+		0 => Ok(()),
+		_ => Err(IoError::last_os_error()),
+	}
+}
+
+/// Lock (Some(mode)) or unlock (None) the range with POSIX `fcntl`.
+#[inline]
+pub fn backend_posix_fcntl(
+	ptr: RawFilePtr,
+	mode: Option<FlockMode>,
+	is_wait: bool,
+	prange: (FlockRangePNum, FlockRangePNum),
+) -> Result<(), IoError> {
+	let cmd = match (mode, is_wait) {
+		(Some(..), true) => __internal_flags::WAIT_RANGE_LOCK,
+		(Some(..), false) => __internal_flags::TRY_RANGE_LOCK,
+		(None, _) => __internal_flags::RANGE_UNLOCK,
+	};
+
+	raw_fcntl(ptr, cmd, fcntl_l_type(mode), prange)
+}
+
+/// Lock (Some(mode)) or unlock (None) the range with OFD `fcntl`.
+#[cfg(target_os = "linux")]
+#[inline]
+pub fn backend_ofd_fcntl(
+	ptr: RawFilePtr,
+	mode: Option<FlockMode>,
+	is_wait: bool,
+	prange: (FlockRangePNum, FlockRangePNum),
+) -> Result<(), IoError> {
+	let cmd = match (mode, is_wait) {
+		(Some(..), true) => __internal_flags::WAIT_OFD_LOCK,
+		(Some(..), false) => __internal_flags::TRY_OFD_LOCK,
+		(None, _) => __internal_flags::OFD_UNLOCK,
+	};
+
+	raw_fcntl(ptr, cmd, fcntl_l_type(mode), prange)
+}
+
+/// Find the lock that prevents setting the lock of the range (F_GETLK, F_OFD_GETLK).
+#[inline]
+fn raw_getlk(
+	ptr: RawFilePtr,
+	cmd: __internal_flags::LibcFlag,
+	mode: FlockMode,
+	prange: (FlockRangePNum, FlockRangePNum),
+) -> Result<Option<FlockConflict>, IoError> {
+	let mut raw_flock = make_raw_flock(fcntl_l_type(Some(mode)), prange);
+	let raw_flock_ptr = &mut raw_flock as *mut libc::flock;
+
+	if unsafe { libc::fcntl(ptr, cmd, raw_flock_ptr) } == -1 {
+		return Err(IoError::last_os_error());
+	}
+
+	let mode = match raw_flock.l_type as __internal_flags::LibcFlag {
+		__internal_flags::UNLOCK_RANGE => return Ok(None),
+		__internal_flags::EXCLUSIVE_RANGE => FlockMode::Exclusive,
+		_ => FlockMode::Shared,
+	};
+	let start = raw_flock.l_start;
+	let end = match raw_flock.l_len {
+		0 => <FlockRangePNum as FlockRangePNumBeh>::MAX,
+		len => start + len - 1,
+	};
+	// OFD locks have no owning process (l_pid == -1).
+	let pid = match raw_flock.l_pid {
+		pid if pid > 0 => Some(pid),
+		_ => None,
+	};
+
+	Ok(Some(FlockConflict::new(mode, (start, end), pid)))
+}
+
+/// Find (F_GETLK) the lock that prevents setting the lock of the range.
+#[inline]
+pub fn backend_posix_getlk(
+	ptr: RawFilePtr,
+	mode: FlockMode,
+	prange: (FlockRangePNum, FlockRangePNum),
+) -> Result<Option<FlockConflict>, IoError> {
+	raw_getlk(ptr, libc::F_GETLK, mode, prange)
+}
+
+/// Find (F_OFD_GETLK) the lock that prevents setting the lock of the range.
+#[cfg(target_os = "linux")]
+#[inline]
+pub fn backend_ofd_getlk(
+	ptr: RawFilePtr,
+	mode: FlockMode,
+	prange: (FlockRangePNum, FlockRangePNum),
+) -> Result<Option<FlockConflict>, IoError> {
+	raw_getlk(ptr, __internal_flags::OFD_GETLK, mode, prange)
+}
@@ -0,0 +1,142 @@
+//! Waiting for locks in tokio without blocking the runtime workers (Unix only).
+//!
+//! The lock is requested without waiting (`LOCK_NB`) and the task sleeps in the
+//! tokio timer between attempts (`future::FlockFuture`), no thread is blocked.
+//! There is no background wait: dropping the future stops the attempts, it never
+//! leaves a lock and never releases a lock of the caller. The runtime must have
+//! the timer enabled (`#[tokio::main]`, `Builder::enable_time`).
+//!
+//! ```rust
+//! use cluFlock::tokio_flock::TokioFlock;
+//! use tokio::io::AsyncWriteExt;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), std::io::Error> {
+//! 	let file = tokio::fs::File::create("./file").await?;
+//!
+//! 	let mut lock = file.lock_exclusive().await?;
+//! 	// lock, type: FlockLock<tokio::fs::File, Exclusive>
+//! 	lock.write_all(b"data").await?;
+//! 	lock.flush().await?;
+//!
+//! 	let file = lock.unlock_data().0;
+//! 	let lock = file.lock_shared().await?;
+//! 	println!("{:?}", lock);
+//!
+//! 	Ok(())
+//! } // auto unlock
+//! ```
+
+use crate::element::FlockElement;
+use crate::err::FlockError;
+use crate::err::IoError;
+use crate::future::FlockFuture;
+use crate::mode::Exclusive;
+use crate::mode::FlockLockMode;
+use crate::mode::Shared;
+use crate::sys::RawFilePtr;
+use crate::unlock::WaitFlockUnlock;
+use crate::FlockLock;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::Context;
+use core::task::Poll;
+use core::time::Duration;
+use std::io::SeekFrom;
+use std::os::unix::io::AsRawFd;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncSeek;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+
+/// Pause between attempts to get a lock.
+const LOCK_PAUSE: Duration = Duration::from_millis(10);
+
+impl FlockElement for tokio::fs::File {
+	type FilePtr = RawFilePtr;
+
+	#[inline(always)]
+	fn as_file_ptr(&self) -> Self::FilePtr {
+		AsRawFd::as_raw_fd(self)
+	}
+}
+
+/// Asynchronous waiting for whole file locks in tokio.
+pub trait TokioFlock
+where
+	Self: FlockElement<FilePtr = RawFilePtr> + Send + Sized,
+{
+	/// Wait for an exclusive lock without blocking the runtime workers.
+	fn lock_exclusive(
+		self,
+	) -> impl Future<Output = Result<FlockLock<Self, Exclusive>, FlockError<Self>>> + Send {
+		FlockFuture::exclusive(self, tokio::time::sleep, LOCK_PAUSE)
+	}
+
+	/// Wait for an shared lock without blocking the runtime workers.
+	fn lock_shared(
+		self,
+	) -> impl Future<Output = Result<FlockLock<Self, Shared>, FlockError<Self>>> + Send {
+		FlockFuture::shared(self, tokio::time::sleep, LOCK_PAUSE)
+	}
+}
+
+impl<T> TokioFlock for T where T: FlockElement<FilePtr = RawFilePtr> + Send {}
+
+/// Read access is available under any lock.
+impl<T, M> AsyncRead for FlockLock<T, M>
+where
+	T: FlockElement + WaitFlockUnlock + AsyncRead + Unpin,
+	M: FlockLockMode + Unpin,
+{
+	#[inline(always)]
+	fn poll_read(
+		self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<Result<(), IoError>> {
+		Pin::new(self.get_mut().as_mut_data_unchecked()).poll_read(cx, buf)
+	}
+}
+
+/// Seek is available under any lock.
+impl<T, M> AsyncSeek for FlockLock<T, M>
+where
+	T: FlockElement + WaitFlockUnlock + AsyncSeek + Unpin,
+	M: FlockLockMode + Unpin,
+{
+	#[inline(always)]
+	fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> Result<(), IoError> {
+		Pin::new(self.get_mut().as_mut_data_unchecked()).start_seek(position)
+	}
+
+	#[inline(always)]
+	fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<u64, IoError>> {
+		Pin::new(self.get_mut().as_mut_data_unchecked()).poll_complete(cx)
+	}
+}
+
+/// Write access is available only under an exclusive lock.
+impl<T> AsyncWrite for FlockLock<T, Exclusive>
+where
+	T: FlockElement + WaitFlockUnlock + AsyncWrite + Unpin,
+{
+	#[inline(always)]
+	fn poll_write(
+		self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &[u8],
+	) -> Poll<Result<usize, IoError>> {
+		Pin::new(self.get_mut().as_mut_data()).poll_write(cx, buf)
+	}
+
+	#[inline(always)]
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), IoError>> {
+		Pin::new(self.get_mut().as_mut_data()).poll_flush(cx)
+	}
+
+	#[inline(always)]
+	fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), IoError>> {
+		Pin::new(self.get_mut().as_mut_data()).poll_shutdown(cx)
+	}
+}
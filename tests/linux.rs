@@ -0,0 +1,295 @@
+#[cfg(feature = "std")]
+#[cfg(target_os = "linux")]
+mod linux {
+	use cluFlock::backend::AutoBackend;
+	use cluFlock::backend::BackendFile;
+	use cluFlock::backend::LockBackendKind;
+	use cluFlock::conflict::query_conflict;
+	use cluFlock::conflict::query_ofd_conflict;
+	use cluFlock::mode::FlockMode;
+	use cluFlock::ofd::OfdFile;
+	use cluFlock::proc_locks::list_fd_locks;
+	use cluFlock::proc_locks::list_file_locks;
+	use cluFlock::proc_locks::ProcLockKind;
+	use cluFlock::ExclusiveFlock;
+	use cluFlock::ExclusiveFlockRange;
+	use cluFlock::SharedFlock;
+	use cluFlock::SharedFlockRange;
+	use core::ops::Deref;
+	use std::fs::File;
+	use std::fs::OpenOptions;
+	use std::path::Path;
+
+	struct AutoRemoveFile<'a>(&'a Path, File);
+
+	impl<'a> AutoRemoveFile<'a> {
+		pub fn file_create(path: &'a Path) -> Self {
+			let file = OpenOptions::new()
+				.read(true)
+				.write(true)
+				.create(true)
+				.truncate(true)
+				.open(path)
+				.unwrap();
+
+			AutoRemoveFile(path, file)
+		}
+
+		/// Open the same file again (new open file description).
+		pub fn reopen(&self) -> File {
+			OpenOptions::new()
+				.read(true)
+				.write(true)
+				.open(self.0)
+				.unwrap()
+		}
+	}
+
+	impl<'a> Deref for AutoRemoveFile<'a> {
+		type Target = File;
+
+		#[inline(always)]
+		fn deref(&self) -> &Self::Target {
+			&self.1
+		}
+	}
+
+	impl<'a> Drop for AutoRemoveFile<'a> {
+		#[inline]
+		fn drop(&mut self) {
+			std::fs::remove_file(self.0).unwrap();
+		}
+	}
+
+	#[test]
+	fn linux_ofd_exclusive_check() {
+		let file = AutoRemoveFile::file_create(Path::new("./del_linux_ofd_exclusive_check"));
+		let file2 = file.reopen();
+
+		let exclusive = match ExclusiveFlock::try_lock(OfdFile::new(&*file)) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		// OFD locks conflict inside the process (unlike POSIX fcntl).
+		match SharedFlock::try_lock(OfdFile::new(&file2)) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => assert!(e.is_would_block()),
+		}
+		assert!(!OfdFile::new(&file2).test_shared_lock(0usize).unwrap());
+
+		// Closing another descriptor of the file does not release OFD locks (unlike POSIX fcntl).
+		drop(file.reopen());
+		if let Ok(a) = ExclusiveFlock::try_lock(OfdFile::new(&file2)) {
+			panic!("Strange behavior, the file is already locked, {:?}", a);
+		}
+
+		drop(exclusive);
+		match ExclusiveFlock::try_lock(OfdFile::new(&file2)) {
+			Ok(_a) => {}
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		}
+
+		drop(file);
+	}
+
+	#[test]
+	fn linux_ofd_range_check() {
+		let file = AutoRemoveFile::file_create(Path::new("./del_linux_ofd_range_check"));
+		let file2 = file.reopen();
+
+		let exclusive_range =
+			match ExclusiveFlockRange::try_lock_range(OfdFile::new(&*file), (0usize, 9usize)) {
+				Ok(a) => a,
+				Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+			};
+		let shared_range = match SharedFlockRange::try_lock_range(OfdFile::new(&*file), 20usize..) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		let ofd_file2 = OfdFile::new(&file2);
+		assert!(!ofd_file2.test_shared_lock(5usize).unwrap());
+		assert!(ofd_file2.test_exclusive_lock((10usize, 19usize)).unwrap());
+		assert!(ofd_file2.test_shared_lock(100usize).unwrap());
+		assert!(!ofd_file2.test_exclusive_lock(100usize).unwrap());
+
+		match ExclusiveFlockRange::try_lock_range(ofd_file2, (10usize, 19usize)) {
+			Ok(_a) => {}
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		}
+
+		drop(exclusive_range);
+		drop(shared_range);
+		assert!(ofd_file2.test_exclusive_lock(0usize..100).unwrap());
+
+		drop(file);
+	}
+
+	#[test]
+	fn linux_backend_kind_check() {
+		let file = AutoRemoveFile::file_create(Path::new("./del_linux_backend_kind_check"));
+		let file2 = file.reopen();
+
+		assert_eq!(
+			"fcntl".parse::<LockBackendKind>().unwrap(),
+			LockBackendKind::Posix
+		);
+		assert!("lockf".parse::<LockBackendKind>().is_err());
+
+		let flock_backend: LockBackendKind = "flock".parse().unwrap();
+		let exclusive =
+			match ExclusiveFlock::try_lock(BackendFile::with_backend(&*file, flock_backend)) {
+				Ok(a) => a,
+				Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+			};
+
+		// The flock backend conflicts with the default flock locks.
+		match SharedFlock::try_lock(&file2) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => assert!(e.is_would_block()),
+		}
+
+		// The flock backend does not support ranges.
+		match ExclusiveFlockRange::try_lock_range(
+			BackendFile::with_backend(&file2, flock_backend),
+			0usize..10,
+		) {
+			Ok(a) => panic!("Strange behavior, range lock with flock backend, {:?}", a),
+			Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput),
+		}
+
+		drop(exclusive);
+		match SharedFlock::try_lock(BackendFile::<_, AutoBackend>::new(&file2)) {
+			Ok(_a) => {}
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		}
+
+		drop(file);
+	}
+
+	#[test]
+	fn linux_backend_auto_range_check() {
+		let file = AutoRemoveFile::file_create(Path::new("./del_linux_backend_auto_range_check"));
+		let file2 = file.reopen();
+
+		// Auto uses OFD locks for ranges, they conflict inside the process.
+		let exclusive_range = match ExclusiveFlockRange::try_lock_range(
+			BackendFile::<_>::new(&*file),
+			(0usize, 9usize),
+		) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+		assert!(!OfdFile::new(&file2).test_shared_lock(5usize).unwrap());
+		match SharedFlockRange::try_lock_range(BackendFile::<_>::new(&file2), 5usize) {
+			Ok(a) => panic!("Strange behavior, the range is already locked, {:?}", a),
+			Err(e) => assert!(e.is_would_block()),
+		}
+
+		// The whole file and a range are locked by the same mechanism.
+		match ExclusiveFlock::try_lock(BackendFile::<_>::new(&file2)) {
+			Ok(a) => panic!("Strange behavior, the range is already locked, {:?}", a),
+			Err(e) => assert!(e.is_would_block()),
+		}
+
+		drop(exclusive_range);
+		assert!(OfdFile::new(&file2)
+			.test_exclusive_lock(0usize..100)
+			.unwrap());
+
+		let exclusive = match ExclusiveFlock::try_lock(BackendFile::<_>::new(&*file)) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+		match ExclusiveFlockRange::try_lock_range(BackendFile::<_>::new(&file2), 5usize) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => assert!(e.is_would_block()),
+		}
+		drop(exclusive);
+
+		drop(file);
+	}
+
+	#[test]
+	fn linux_query_ofd_conflict_check() {
+		let file = AutoRemoveFile::file_create(Path::new("./del_linux_query_ofd_conflict_check"));
+		let file2 = file.reopen();
+
+		let exclusive_range =
+			match ExclusiveFlockRange::try_lock_range(OfdFile::new(&*file), (0usize, 9usize)) {
+				Ok(a) => a,
+				Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+			};
+
+		// OFD locks are visible to both F_GETLK and F_OFD_GETLK, but have no pid.
+		let conflict = query_conflict(&file2, FlockMode::Shared, 5usize)
+			.unwrap()
+			.unwrap();
+		assert_eq!(conflict.mode(), FlockMode::Exclusive);
+		assert_eq!(conflict.range(), (0, 9));
+		assert_eq!(conflict.pid(), None);
+		assert_eq!(
+			conflict.to_string(),
+			"locked by open file description (exclusive, bytes 0..=9)"
+		);
+		assert_eq!(
+			query_ofd_conflict(&file2, FlockMode::Shared, 5usize).unwrap(),
+			Some(conflict)
+		);
+		assert_eq!(
+			query_ofd_conflict(&file2, FlockMode::Exclusive, 10usize..).unwrap(),
+			None
+		);
+
+		drop(exclusive_range);
+		assert_eq!(
+			query_conflict(&file2, FlockMode::Exclusive, 0usize..).unwrap(),
+			None
+		);
+
+		drop(file);
+	}
+
+	#[test]
+	fn linux_proc_locks_check() {
+		let path = Path::new("./del_linux_proc_locks_check");
+		let file = AutoRemoveFile::file_create(path);
+		let file2 = file.reopen();
+		let pid = std::process::id() as libc::pid_t;
+
+		assert!(list_file_locks(path).unwrap().is_empty());
+
+		let exclusive = match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+		let shared_range = match SharedFlockRange::try_lock_range(&file2, (10usize, 19usize)) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		let mut locks = list_file_locks(path).unwrap();
+		locks.sort_by(|a, b| a.kind().cmp(b.kind()));
+		assert_eq!(locks.len(), 2);
+		assert_eq!(locks[0].kind(), &ProcLockKind::Flock);
+		assert_eq!(locks[0].mode(), Some(FlockMode::Exclusive));
+		assert_eq!(locks[0].pid(), Some(pid));
+		assert!(locks[0].is_to_end());
+		assert_eq!(locks[1].kind(), &ProcLockKind::Posix);
+		assert_eq!(locks[1].mode(), Some(FlockMode::Shared));
+		assert_eq!(locks[1].range(), (10, 19));
+		assert_eq!(list_file_locks(&*file).unwrap().len(), locks.len());
+
+		// fdinfo only shows the locks set through the descriptor.
+		let fd_locks = list_fd_locks(&*file).unwrap();
+		assert_eq!(fd_locks.len(), 1);
+		assert_eq!(fd_locks[0].kind(), &ProcLockKind::Flock);
+
+		drop(exclusive);
+		drop(shared_range);
+		assert!(list_file_locks(&*file).unwrap().is_empty());
+
+		drop(file);
+	}
+}
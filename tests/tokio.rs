@@ -0,0 +1,133 @@
+#[cfg(feature = "tokio")]
+#[cfg(unix)]
+mod tokio_flock {
+	use cluFlock::tokio_flock::TokioFlock;
+	use cluFlock::ExclusiveFlock;
+	use cluFlock::SharedFlock;
+	use core::ops::Deref;
+	use std::fs::File;
+	use std::path::Path;
+	use std::time::Duration;
+	use tokio::io::AsyncReadExt;
+	use tokio::io::AsyncSeekExt;
+	use tokio::io::AsyncWriteExt;
+
+	struct AutoRemoveFile<'a>(&'a Path, File);
+
+	impl<'a> AutoRemoveFile<'a> {
+		pub fn file_create(path: &'a Path) -> Self {
+			AutoRemoveFile(path, File::create(path).unwrap())
+		}
+
+		/// Open the same file again (new open file description).
+		pub async fn reopen(&self) -> tokio::fs::File {
+			tokio::fs::OpenOptions::new()
+				.read(true)
+				.write(true)
+				.open(self.0)
+				.await
+				.unwrap()
+		}
+	}
+
+	impl<'a> Deref for AutoRemoveFile<'a> {
+		type Target = File;
+
+		#[inline(always)]
+		fn deref(&self) -> &Self::Target {
+			&self.1
+		}
+	}
+
+	impl<'a> Drop for AutoRemoveFile<'a> {
+		#[inline]
+		fn drop(&mut self) {
+			std::fs::remove_file(self.0).unwrap();
+		}
+	}
+
+	#[tokio::test(flavor = "current_thread")]
+	async fn tokio_wait_lock_check() {
+		let file = AutoRemoveFile::file_create(Path::new("./del_tokio_wait_lock_check"));
+		let file2 = file.reopen().await;
+
+		let exclusive = match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		// The only worker keeps running while the lock is awaited.
+		let waiter = tokio::spawn(file2.lock_exclusive());
+		tokio::time::sleep(Duration::from_millis(100)).await;
+		assert!(!waiter.is_finished());
+
+		drop(exclusive);
+		let mut lock = match waiter.await.unwrap() {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		};
+		if let Ok(a) = SharedFlock::try_lock(&*file) {
+			panic!("Strange behavior, the file is already locked, {:?}", a);
+		}
+
+		lock.write_all(b"tokio").await.unwrap();
+		lock.flush().await.unwrap();
+
+		// Shared locks, read access.
+		let file2 = lock.unlock_data().0;
+		let mut shared = match file2.lock_shared().await {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		};
+		let shared2 = match TokioFlock::lock_shared(&*file).await {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		};
+		shared.rewind().await.unwrap();
+		let mut data = String::new();
+		shared.read_to_string(&mut data).await.unwrap();
+		assert_eq!(data, "tokio");
+
+		drop(shared2);
+		drop(shared);
+		drop(file);
+	}
+
+	#[tokio::test(flavor = "current_thread")]
+	async fn tokio_drop_future_check() {
+		let file = AutoRemoveFile::file_create(Path::new("./del_tokio_drop_future_check"));
+		let file2 = file.reopen().await;
+
+		let exclusive = match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		// The future is dropped while the lock is awaited.
+		let timeout = Duration::from_millis(50);
+		if let Ok(a) = tokio::time::timeout(timeout, (&file2).lock_exclusive()).await {
+			panic!("Strange behavior, the file is already locked, {:?}", a);
+		}
+		drop(exclusive);
+
+		// Nothing keeps waiting for the lock, the file stays free.
+		tokio::time::sleep(Duration::from_millis(200)).await;
+		match ExclusiveFlock::try_lock(&*file) {
+			Ok(_a) => {}
+			Err(e) => panic!(
+				"Strange behavior, the dropped future has left a lock, {:?}",
+				e
+			),
+		}
+		match ExclusiveFlock::try_lock(&file2) {
+			Ok(_a) => {}
+			Err(e) => panic!(
+				"Strange behavior, the dropped future has left a lock, {:?}",
+				e
+			),
+		}
+
+		drop(file2);
+		drop(file);
+	}
+}
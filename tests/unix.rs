@@ -1,13 +1,19 @@
 #[cfg(feature = "std")]
 #[cfg(unix)]
 mod unix {
+	use cluFlock::conflict::query_conflict;
+	use cluFlock::mode::FlockMode;
+	use cluFlock::range::FlockRangeFull;
 	use cluFlock::ExclusiveFlock;
+	use cluFlock::ExclusiveFlockRange;
 	use cluFlock::SharedFlock;
+	use cluFlock::SharedFlockRange;
 	use core::ops::Deref;
 	use std::borrow::Cow;
 	use std::ffi::OsStr;
 	use std::ffi::OsString;
 	use std::fs::File;
+	use std::fs::OpenOptions;
 	use std::path::Path;
 	use std::process::Child;
 	use std::process::Command;
@@ -21,6 +27,18 @@ mod unix {
 			AutoRemoveFile(path, File::create(path).unwrap())
 		}
 
+		pub fn file_create_rw(path: &'a Path) -> Self {
+			let file = OpenOptions::new()
+				.read(true)
+				.write(true)
+				.create(true)
+				.truncate(true)
+				.open(path)
+				.unwrap();
+
+			AutoRemoveFile(path, file)
+		}
+
 		#[inline(always)]
 		pub fn as_path(&self) -> &Path {
 			self.0
@@ -87,6 +105,27 @@ mod unix {
 		}
 	}
 
+	/// Run `f` in a forked child process and get its exit code.
+	/// (POSIX fcntl locks do not conflict inside one process.)
+	fn fork_exit_code(f: impl FnOnce() -> i32) -> i32 {
+		match unsafe { libc::fork() } {
+			-1 => panic!("Failed to fork, {:?}", std::io::Error::last_os_error()),
+			0 => {
+				let code = f();
+				unsafe { libc::_exit(code) }
+			}
+			pid => {
+				let mut status = 0;
+				if unsafe { libc::waitpid(pid, &mut status, 0) } != pid {
+					panic!("Undefined behavior, the process should have ended correctly.");
+				}
+				assert!(libc::WIFEXITED(status));
+
+				libc::WEXITSTATUS(status)
+			}
+		}
+	}
+
 	#[test]
 	fn unix_exclusive_two_lock_behavior_onprocess() {
 		let file = AutoRemoveFile::file_create(Path::new("./del_unix_two_lock_behavior"));
@@ -192,4 +231,1366 @@ mod unix {
 
 		drop(file);
 	}
+
+	#[test]
+	fn unix_range_check() {
+		let file = AutoRemoveFile::file_create_rw(Path::new("./del_unix_range_check"));
+
+		let exclusive_range = match ExclusiveFlockRange::try_lock_range(&*file, 0usize..10) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+		let shared_range = match SharedFlockRange::try_lock_range(&*file, 20usize..) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		// exclusive (0..=10): another process can not lock it.
+		let code = fork_exit_code(|| {
+			let exclusive = ExclusiveFlockRange::try_lock_range(&*file, 5usize);
+
+			match exclusive {
+				Ok(..) => 0,
+				Err(e) if e.is_would_block() => 1,
+				Err(..) => 2,
+			}
+		});
+		assert_eq!(code, 1);
+
+		// shared (20..=max): another process can only share it.
+		let code = fork_exit_code(|| {
+			let exclusive = ExclusiveFlockRange::try_lock_range(&*file, 30usize..40);
+			let shared = SharedFlockRange::try_lock_range(&*file, 30usize..40);
+
+			match (exclusive, shared) {
+				(Err(e), Ok(..)) if e.is_would_block() => 0,
+				_ => 1,
+			}
+		});
+		assert_eq!(code, 0);
+
+		// free range (11..=19)
+		let code = fork_exit_code(|| {
+			let exclusive = ExclusiveFlockRange::try_lock_range(&*file, 11usize..19);
+
+			match exclusive {
+				Ok(..) => 0,
+				Err(..) => 1,
+			}
+		});
+		assert_eq!(code, 0);
+
+		if let Err(e) = exclusive_range.unlock() {
+			panic!("Strange behavior, failed to unlock, {:?}", e);
+		}
+		drop(shared_range);
+
+		let code = fork_exit_code(|| {
+			let exclusive =
+				ExclusiveFlockRange::try_lock_range(&*file, FlockRangeFull::<usize>::full());
+
+			match exclusive {
+				Ok(..) => 0,
+				Err(..) => 1,
+			}
+		});
+		assert_eq!(code, 0);
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_range_invalid() {
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_range_invalid"));
+
+		match ExclusiveFlockRange::try_lock_range(&*file, (10usize, 2usize)) {
+			Ok(a) => panic!("Strange behavior, invalid range is locked, {:?}", a),
+			Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput),
+		}
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_query_conflict_check() {
+		let file = AutoRemoveFile::file_create_rw(Path::new("./del_unix_query_conflict_check"));
+
+		let exclusive_range = match ExclusiveFlockRange::try_lock_range(&*file, 0usize..) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		// Own POSIX locks never conflict.
+		assert_eq!(
+			query_conflict(&*file, FlockMode::Exclusive, 0usize..).unwrap(),
+			None
+		);
+
+		let parent_pid = std::process::id() as libc::pid_t;
+		let code = fork_exit_code(|| {
+			let conflict = match query_conflict(&*file, FlockMode::Shared, 5usize..10) {
+				Ok(Some(a)) => a,
+				_ => return 1,
+			};
+
+			match (
+				conflict.mode(),
+				conflict.range().0,
+				conflict.is_to_end(),
+				conflict.pid(),
+			) {
+				(FlockMode::Exclusive, 0, true, Some(pid)) if pid == parent_pid => {}
+				_ => return 2,
+			}
+			if conflict.to_string()
+				!= format!("locked by pid {} (exclusive, bytes 0..)", parent_pid)
+			{
+				return 3;
+			}
+
+			0
+		});
+		assert_eq!(code, 0);
+
+		drop(exclusive_range);
+		let shared_range = match SharedFlockRange::try_lock_range(&*file, (10usize, 19usize)) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		let code = fork_exit_code(|| {
+			let shared = query_conflict(&*file, FlockMode::Shared, 0usize..);
+			let exclusive = query_conflict(&*file, FlockMode::Exclusive, 0usize..);
+
+			match (shared, exclusive) {
+				(Ok(None), Ok(Some(a)))
+					if a.range() == (10, 19) && a.mode() == FlockMode::Shared =>
+				{
+					0
+				}
+				_ => 1,
+			}
+		});
+		assert_eq!(code, 0);
+
+		drop(shared_range);
+		drop(file);
+	}
+
+	#[test]
+	fn unix_upgrade_downgrade_check() {
+		let file = AutoRemoveFile::file_create_rw(Path::new("./del_unix_upgrade_downgrade_check"));
+		let guard_file =
+			AutoRemoveFile::file_create(Path::new("./del_unix_upgrade_downgrade_guard"));
+		// flock locks of different open file descriptions conflict.
+		let file2 = File::open(file.as_path()).unwrap();
+
+		let shared = match SharedFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+		let shared2 = match SharedFlock::try_lock(&file2) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		// another shared lock: the upgrade fails, the original guard is returned intact.
+		let shared = match shared.try_upgrade() {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => {
+				assert!(e.is_would_block());
+				assert!(!e.is_lost());
+				match e.into_lock() {
+					Ok(a) => a,
+					Err(_) => panic!("Strange behavior, the shared lock is lost"),
+				}
+			}
+		};
+		drop(shared2);
+		if let Ok(a) = ExclusiveFlock::try_lock(&file2) {
+			panic!("Strange behavior, the shared lock is lost, {:?}", a);
+		}
+
+		let exclusive = match shared.try_upgrade() {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to upgrade, {:?}", e),
+		};
+		if let Ok(a) = SharedFlock::try_lock(&file2) {
+			panic!("Strange behavior, the file is already locked, {:?}", a);
+		}
+
+		let shared = match exclusive.downgrade_with(&*guard_file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to downgrade, {:?}", e),
+		};
+		let shared2 = match SharedFlock::try_lock(&file2) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		};
+		if let Ok(a) = ExclusiveFlock::try_lock(&file2) {
+			panic!("Strange behavior, the file is already locked, {:?}", a);
+		}
+		drop(shared2);
+
+		match shared.upgrade_with(&*guard_file) {
+			Ok(_a) => {}
+			Err(e) => panic!("Strange behavior, failed to upgrade, {:?}", e),
+		}
+
+		drop(guard_file);
+		drop(file);
+	}
+
+	#[test]
+	fn unix_typestate_io_check() {
+		use std::io::Read;
+		use std::io::Seek;
+		use std::io::SeekFrom;
+		use std::io::Write;
+
+		let file = AutoRemoveFile::file_create_rw(Path::new("./del_unix_typestate_io_check"));
+
+		let mut exclusive = match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+		assert_eq!(exclusive.mode(), FlockMode::Exclusive);
+		exclusive.write_all(b"typestate").unwrap();
+		exclusive.flush().unwrap();
+
+		let mut shared = match exclusive.downgrade() {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to downgrade, {:?}", e),
+		};
+		assert_eq!(shared.mode(), FlockMode::Shared);
+		shared.seek(SeekFrom::Start(0)).unwrap();
+		let mut data = String::new();
+		shared.read_to_string(&mut data).unwrap();
+		assert_eq!(data, "typestate");
+
+		drop(shared);
+		drop(file);
+	}
+
+	#[test]
+	fn unix_wait_timeout_check() {
+		use cluFlock::ToFlock;
+		use std::time::Instant;
+
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_wait_timeout_check"));
+		let file2 = File::open(file.as_path()).unwrap();
+
+		let exclusive = match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		let timeout = Duration::from_millis(100);
+		let start = Instant::now();
+		match SharedFlock::wait_lock_timeout(&file2, timeout) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => {
+				assert!(e.is_timed_out());
+				assert!(!e.is_would_block());
+				assert!(start.elapsed() >= timeout);
+
+				// the data is returned in the error
+				assert!(std::ptr::eq(e.into_data(), &file2));
+			}
+		}
+		match (&file2).wait_exclusive_lock_deadline(Instant::now()) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => assert!(e.is_timed_out()),
+		}
+
+		// The lock is released while waiting.
+		std::thread::scope(|scope| {
+			scope.spawn(move || {
+				std::thread::sleep(Duration::from_millis(100));
+				drop(exclusive);
+			});
+
+			match (&file2).wait_exclusive_lock_timeout(Duration::from_secs(30)) {
+				Ok(_a) => {}
+				Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+			}
+		});
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_wait_cancellable_check() {
+		use cluFlock::cancel::FlockCancelToken;
+		use std::time::Instant;
+
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_wait_cancellable_check"));
+		let file2 = File::open(file.as_path()).unwrap();
+
+		let exclusive = match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		let token = FlockCancelToken::new();
+		std::thread::scope(|scope| {
+			let wait_token = token.clone();
+			let file2 = &file2;
+			let waiter = scope.spawn(move || {
+				let start = Instant::now();
+				let result = ExclusiveFlock::wait_lock_cancellable(file2, &wait_token);
+
+				(result.map(|_| ()), start.elapsed())
+			});
+
+			std::thread::sleep(Duration::from_millis(100));
+			token.cancel();
+			match waiter.join().unwrap() {
+				(Ok(()), _) => panic!("Strange behavior, the file is already locked"),
+				(Err(e), elapsed) => {
+					assert!(e.is_cancelled());
+					assert!(!e.is_would_block());
+					assert!(elapsed < Duration::from_secs(5));
+					assert!(std::ptr::eq(e.into_data(), file2));
+				}
+			}
+		});
+
+		// A cancelled token does not leave the lock even if the file is free.
+		drop(exclusive);
+		assert!(token.is_cancelled());
+		match SharedFlock::wait_lock_cancellable(&file2, &token) {
+			Ok(a) => panic!("Strange behavior, the wait is cancelled, {:?}", a),
+			Err(e) => assert!(e.is_cancelled()),
+		}
+		match ExclusiveFlock::try_lock(&*file) {
+			Ok(_a) => {}
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		}
+
+		match ExclusiveFlock::wait_lock_cancellable(&file2, &FlockCancelToken::new()) {
+			Ok(_a) => {}
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		}
+
+		drop(file);
+	}
+
+	/// Send SIGUSR1 (without SA_RESTART) to the thread waiting in `wait`
+	/// until it returns.
+	fn wait_with_signals<R: Send>(wait: impl FnOnce() -> R + Send) -> R {
+		use std::sync::atomic::AtomicBool;
+		use std::sync::atomic::Ordering;
+
+		extern "C" fn empty_handler(_: libc::c_int) {}
+		unsafe {
+			let mut action: libc::sigaction = core::mem::zeroed();
+			action.sa_sigaction = empty_handler as *const () as libc::sighandler_t;
+			action.sa_flags = 0;
+			libc::sigemptyset(&mut action.sa_mask);
+			assert_eq!(
+				libc::sigaction(libc::SIGUSR1, &action, core::ptr::null_mut()),
+				0
+			);
+		}
+
+		let is_done = AtomicBool::new(false);
+		std::thread::scope(|scope| {
+			let (sender, receiver) = std::sync::mpsc::channel();
+			let is_done = &is_done;
+			let waiter = scope.spawn(move || {
+				sender.send(unsafe { libc::pthread_self() }).unwrap();
+				let result = wait();
+				is_done.store(true, Ordering::SeqCst);
+
+				result
+			});
+
+			let thread = receiver.recv().unwrap();
+			while !is_done.load(Ordering::SeqCst) {
+				unsafe {
+					libc::pthread_kill(thread, libc::SIGUSR1);
+				}
+				std::thread::sleep(Duration::from_millis(10));
+			}
+
+			waiter.join().unwrap()
+		})
+	}
+
+	#[test]
+	fn unix_wait_interrupt_check() {
+		use cluFlock::interrupt::FlockInterruptAction;
+		use cluFlock::interrupt::ReturnInterrupted;
+
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_wait_interrupt_check"));
+		let file2 = File::open(file.as_path()).unwrap();
+
+		let exclusive = match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		// Return `Interrupted` on the first signal.
+		match wait_with_signals(|| {
+			SharedFlock::wait_lock_interruptible(&file2, ReturnInterrupted).map(|_| ())
+		}) {
+			Ok(()) => panic!("Strange behavior, the file is already locked"),
+			Err(e) => {
+				assert!(e.is_interrupted());
+				assert!(std::ptr::eq(e.into_data(), &file2));
+			}
+		}
+
+		// The hook decides.
+		let mut interrupts = 0;
+		match wait_with_signals(|| {
+			ExclusiveFlock::wait_lock_interruptible(&file2, || {
+				interrupts += 1;
+				match interrupts < 3 {
+					true => FlockInterruptAction::Retry,
+					false => FlockInterruptAction::Return,
+				}
+			})
+			.map(|_| ())
+		}) {
+			Ok(()) => panic!("Strange behavior, the file is already locked"),
+			Err(e) => assert!(e.is_interrupted()),
+		}
+		assert_eq!(interrupts, 3);
+
+		// By default signals do not interrupt the wait.
+		std::thread::scope(|scope| {
+			scope.spawn(move || {
+				std::thread::sleep(Duration::from_millis(200));
+				drop(exclusive);
+			});
+
+			match wait_with_signals(|| ExclusiveFlock::wait_lock(&file2).map(|_| ())) {
+				Ok(()) => {}
+				Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+			}
+		});
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_flock_future_check() {
+		use cluFlock::future::FlockFuture;
+		use core::future::Future;
+		use core::task::Context;
+		use core::task::Poll;
+		use core::task::Waker;
+
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_flock_future_check"));
+		let file2 = File::open(file.as_path()).unwrap();
+
+		let exclusive = match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		// Without an executor: the timer sleeps right away, the future is ready on the first poll.
+		let mut attempts = 0;
+		let backoff = || {
+			attempts += 1;
+			match attempts < 3 {
+				true => Some(Duration::from_millis(1)),
+				false => None,
+			}
+		};
+		let timer = |pause| {
+			std::thread::sleep(pause);
+			core::future::ready(())
+		};
+		let mut future = core::pin::pin!(FlockFuture::shared(&file2, timer, backoff));
+		let mut cx = Context::from_waker(Waker::noop());
+		match future.as_mut().poll(&mut cx) {
+			Poll::Ready(Ok(a)) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Poll::Ready(Err(e)) => {
+				assert!(e.is_would_block());
+				assert!(std::ptr::eq(e.into_data(), &file2));
+			}
+			Poll::Pending => panic!("Strange behavior, the timer is always ready"),
+		}
+		assert_eq!(attempts, 3);
+
+		// tokio
+		let runtime = tokio::runtime::Builder::new_current_thread()
+			.enable_time()
+			.build()
+			.unwrap();
+		runtime.block_on(async {
+			let waiter = tokio::spawn(FlockFuture::exclusive(
+				File::open(file.as_path()).unwrap(),
+				tokio::time::sleep,
+				Duration::from_millis(5),
+			));
+			tokio::time::sleep(Duration::from_millis(50)).await;
+			assert!(!waiter.is_finished());
+
+			drop(exclusive);
+			match waiter.await.unwrap() {
+				Ok(_a) => {}
+				Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+			}
+		});
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_try_lock_retry_check() {
+		use cluFlock::retry::RetryPolicy;
+		use std::sync::atomic::AtomicU32;
+		use std::sync::atomic::Ordering;
+		use std::sync::Arc;
+
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_try_lock_retry_check"));
+		let file2 = File::open(file.as_path()).unwrap();
+
+		let exclusive = match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => a,
+			Err(e) => panic!("Strange behavior, failed to make a primary lock, {:?}", e),
+		};
+
+		let retries = Arc::new(AtomicU32::new(0));
+		let on_retry_retries = retries.clone();
+		let policy = RetryPolicy::fixed(Duration::from_millis(1))
+			.max_attempts(5)
+			.on_retry(move |stats, pause| {
+				assert_eq!(pause, Duration::from_millis(1));
+				on_retry_retries.store(stats.attempts(), Ordering::SeqCst);
+			});
+		match SharedFlock::try_lock_retry(&file2, policy) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => {
+				assert!(e.is_would_block());
+				assert_eq!(e.retry_stats().unwrap().attempts(), 5);
+				assert!(std::ptr::eq(e.into_data(), &file2));
+			}
+		}
+		assert_eq!(retries.load(Ordering::SeqCst), 4);
+
+		let max_elapsed = Duration::from_millis(50);
+		let policy = RetryPolicy::exponential(Duration::from_millis(1), Duration::from_millis(20))
+			.max_elapsed(max_elapsed);
+		match ExclusiveFlock::try_lock_retry(&file2, policy) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => assert!(e.retry_stats().unwrap().elapsed() >= max_elapsed),
+		}
+
+		// A plain `try_lock` has no statistics.
+		match ExclusiveFlock::try_lock(&file2) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => assert!(e.retry_stats().is_none()),
+		}
+
+		std::thread::scope(|scope| {
+			scope.spawn(move || {
+				std::thread::sleep(Duration::from_millis(100));
+				drop(exclusive);
+			});
+
+			let (base, max) = (Duration::from_millis(1), Duration::from_millis(20));
+			let policy =
+				RetryPolicy::decorrelated_jitter(base, max).max_elapsed(Duration::from_secs(30));
+			match ExclusiveFlock::try_lock_retry(&file2, policy) {
+				Ok(_a) => {}
+				Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+			}
+		});
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_path_lock_check() {
+		use cluFlock::path_lock::PathLock;
+
+		let path = Path::new("./del_unix_path_lock_check");
+		let inside = Path::new("./del_unix_path_lock_check_inside");
+
+		let shared = PathLock::wait_shared_lock(path).unwrap();
+		let shared2 = PathLock::try_shared_lock(path).unwrap();
+		match PathLock::try_exclusive_lock(path) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::WouldBlock),
+		}
+		drop(shared);
+		drop(shared2.into_lock());
+
+		let exclusive = PathLock::try_exclusive_lock(path).unwrap().remove_on_drop();
+		assert!(exclusive.is_remove_on_drop());
+		exclusive.unlock().unwrap();
+		assert!(!path.exists());
+
+		// A file that has replaced the locked one is not removed.
+		let exclusive = PathLock::try_exclusive_lock(path).unwrap().remove_on_drop();
+		std::fs::remove_file(path).unwrap();
+		std::fs::write(path, b"other").unwrap();
+		exclusive.unlock().unwrap();
+		assert_eq!(std::fs::read(path).unwrap(), b"other");
+		std::fs::remove_file(path).unwrap();
+
+		// Processes remove the file after each lock, only one of them at a time
+		// can be inside the lock.
+		let mut pids = Vec::new();
+		for _ in 0..4 {
+			match unsafe { libc::fork() } {
+				-1 => panic!("Failed to fork, {:?}", std::io::Error::last_os_error()),
+				0 => {
+					let code = (|| {
+						for _ in 0..100 {
+							let lock = match PathLock::wait_exclusive_lock(path) {
+								Ok(a) => a.remove_on_drop(),
+								Err(_) => return 1,
+							};
+							if File::create_new(inside).is_err() {
+								return 2;
+							}
+							std::thread::yield_now();
+							if std::fs::remove_file(inside).is_err() {
+								return 3;
+							}
+
+							drop(lock);
+						}
+
+						0
+					})();
+					unsafe { libc::_exit(code) }
+				}
+				pid => pids.push(pid),
+			}
+		}
+
+		for pid in pids {
+			let mut status = 0;
+			assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+			assert!(libc::WIFEXITED(status));
+			assert_eq!(libc::WEXITSTATUS(status), 0);
+		}
+		assert!(!path.exists());
+		assert!(!inside.exists());
+	}
+
+	#[test]
+	fn unix_pid_file_check() {
+		use cluFlock::pid_file::PidFile;
+		use cluFlock::pid_file::PidFileError;
+		use cluFlock::pid_file::PidFileStatus;
+
+		let path = Path::new("./del_unix_pid_file_check");
+		let self_pid = std::process::id() as libc::pid_t;
+		assert_eq!(PidFile::read_running(path).unwrap(), PidFileStatus::Absent);
+
+		let pid_file = PidFile::acquire(path).unwrap();
+		assert_eq!(pid_file.pid(), self_pid);
+		assert_eq!(
+			std::fs::read_to_string(path).unwrap(),
+			format!("{}\n", self_pid)
+		);
+		assert_eq!(
+			PidFile::read_running(path).unwrap(),
+			PidFileStatus::Running(Some(self_pid))
+		);
+		match PidFile::acquire(path) {
+			Err(PidFileError::Running(Some(pid))) => assert_eq!(pid, self_pid),
+			a => panic!("Strange behavior, the file is already locked, {:?}", a),
+		}
+		let code = fork_exit_code(|| match PidFile::acquire(path) {
+			Err(PidFileError::Running(Some(pid))) if pid == self_pid => 0,
+			_ => 1,
+		});
+		assert_eq!(code, 0);
+
+		pid_file.release().unwrap();
+		assert_eq!(PidFile::read_running(path).unwrap(), PidFileStatus::Absent);
+
+		// The process ends without removing the file.
+		let code = fork_exit_code(|| match PidFile::acquire(path) {
+			Ok(a) => {
+				core::mem::forget(a);
+				0
+			}
+			Err(_) => 1,
+		});
+		assert_eq!(code, 0);
+		let stale_pid = match PidFile::read_running(path).unwrap() {
+			PidFileStatus::Stale(pid) => pid,
+			a => panic!("Strange behavior, the file must be stale, {:?}", a),
+		};
+		assert_ne!(stale_pid, self_pid);
+
+		// Created but not locked or written yet: not stale.
+		std::fs::write(path, b"").unwrap();
+		assert_eq!(PidFile::read_running(path).unwrap(), PidFileStatus::Unknown);
+		std::fs::write(path, b"not a pid").unwrap();
+		assert_eq!(PidFile::read_running(path).unwrap(), PidFileStatus::Unknown);
+
+		let pid_file = PidFile::acquire(path).unwrap();
+		assert_eq!(
+			PidFile::read_running(path).unwrap(),
+			PidFileStatus::Running(Some(self_pid))
+		);
+		drop(pid_file);
+		assert!(!path.exists());
+	}
+
+	#[test]
+	fn unix_single_instance_check() {
+		use cluFlock::single_instance::SingleInstance;
+
+		let dir = Path::new("./del_unix_single_instance_check");
+		std::fs::create_dir(dir).unwrap();
+
+		let primary = match SingleInstance::acquire_in(dir, "app").unwrap() {
+			SingleInstance::Primary(a) => a,
+			a => panic!(
+				"Strange behavior, failed to make a primary instance, {:?}",
+				a
+			),
+		};
+		let receiver = primary.receiver().unwrap();
+
+		let secondary = SingleInstance::acquire_in(dir, "app").unwrap();
+		assert!(!secondary.is_primary());
+		let code = fork_exit_code(|| match SingleInstance::acquire_in(dir, "app") {
+			Ok(SingleInstance::Secondary(a)) => match a.send(b"from child") {
+				Ok(()) => 0,
+				Err(_) => 2,
+			},
+			_ => 1,
+		});
+		assert_eq!(code, 0);
+		assert_eq!(receiver.recv().unwrap(), b"from child");
+
+		if let SingleInstance::Secondary(a) = secondary {
+			a.send(b"").unwrap();
+		}
+		assert_eq!(receiver.recv().unwrap(), b"");
+
+		// A silent secondary does not block the receiver.
+		let silent = std::os::unix::net::UnixStream::connect(primary.as_socket_path()).unwrap();
+		if let Ok(a) = receiver.recv() {
+			panic!("Strange behavior, nothing was sent, {:?}", a);
+		}
+		drop(silent);
+		if let SingleInstance::Secondary(a) = SingleInstance::acquire_in(dir, "app").unwrap() {
+			a.send(b"after silent").unwrap();
+		}
+		assert_eq!(receiver.recv().unwrap(), b"after silent");
+
+		// The next primary after the first one is dropped.
+		drop(primary);
+		assert!(SingleInstance::acquire_in(dir, "app").unwrap().is_primary());
+		assert!(SingleInstance::acquire_in(dir, "../app").is_err());
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn unix_dir_lock_check() {
+		use cluFlock::dir_lock::LockedDir;
+		use std::io::Read;
+		use std::io::Write;
+
+		let dir = Path::new("./del_unix_dir_lock_check");
+		let renamed = Path::new("./del_unix_dir_lock_check_renamed");
+		std::fs::create_dir(dir).unwrap();
+
+		let shared = LockedDir::wait_shared_lock(dir).unwrap();
+		let shared2 = LockedDir::try_shared_lock(dir).unwrap();
+		match LockedDir::try_exclusive_lock(dir) {
+			Ok(a) => panic!("Strange behavior, the directory is already locked, {:?}", a),
+			Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::WouldBlock),
+		}
+		drop(shared);
+		drop(shared2);
+
+		let exclusive = LockedDir::try_exclusive_lock(dir).unwrap();
+		if let Ok(a) = SharedFlock::try_lock(LockedDir::open(dir).unwrap()) {
+			panic!("Strange behavior, the directory is already locked, {:?}", a);
+		}
+		let code = fork_exit_code(|| match LockedDir::try_shared_lock(dir) {
+			Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => 0,
+			_ => 1,
+		});
+		assert_eq!(code, 0);
+
+		// Files are opened relative to the locked directory, even after renaming.
+		exclusive
+			.create_file("data")
+			.unwrap()
+			.write_all(b"dir")
+			.unwrap();
+		std::fs::rename(dir, renamed).unwrap();
+		let mut data = String::new();
+		exclusive
+			.open_file("data")
+			.unwrap()
+			.read_to_string(&mut data)
+			.unwrap();
+		assert_eq!(data, "dir");
+
+		exclusive.create_dir("sub").unwrap();
+		assert!(exclusive
+			.open_dir("sub")
+			.unwrap()
+			.metadata()
+			.unwrap()
+			.is_dir());
+		exclusive.remove_dir("sub").unwrap();
+		exclusive.remove_file("data").unwrap();
+		exclusive.sync_all().unwrap();
+		assert!(exclusive.open_file("data").is_err());
+		drop(exclusive);
+
+		std::fs::remove_dir(renamed).unwrap();
+	}
+
+	#[test]
+	fn unix_registry_check() {
+		use cluFlock::registry::RegistryLock;
+		use std::sync::atomic::AtomicUsize;
+		use std::sync::atomic::Ordering;
+
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_registry_check"));
+		let path = file.as_path();
+
+		// Threads with separate opens get the same semantics as processes.
+		let shared = RegistryLock::wait_shared_lock(path).unwrap();
+		let shared2 = std::thread::scope(|s| {
+			s.spawn(|| RegistryLock::try_shared_lock(path).unwrap())
+				.join()
+				.unwrap()
+		});
+		match RegistryLock::try_exclusive_lock(path) {
+			Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+			Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::WouldBlock),
+		}
+		let code = fork_exit_code(|| match SharedFlock::try_lock(File::open(path).unwrap()) {
+			Ok(_a) => match ExclusiveFlock::try_lock(File::open(path).unwrap()) {
+				Err(e) if e.is_would_block() => 0,
+				_ => 2,
+			},
+			Err(_) => 1,
+		});
+		assert_eq!(code, 0);
+
+		// The flock lock is released by the last holder.
+		drop(shared);
+		let code = fork_exit_code(
+			|| match ExclusiveFlock::try_lock(File::open(path).unwrap()) {
+				Err(e) if e.is_would_block() => 0,
+				_ => 1,
+			},
+		);
+		assert_eq!(code, 0);
+		shared2.unlock().unwrap();
+		let code = fork_exit_code(
+			|| match ExclusiveFlock::try_lock(File::open(path).unwrap()) {
+				Ok(_a) => 0,
+				Err(_) => 1,
+			},
+		);
+		assert_eq!(code, 0);
+
+		// Only one thread at a time holds the exclusive lock.
+		let inside = AtomicUsize::new(0);
+		std::thread::scope(|s| {
+			for _ in 0..4 {
+				s.spawn(|| {
+					for _ in 0..100 {
+						let lock = RegistryLock::wait_exclusive_lock(path).unwrap();
+						assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+						std::thread::yield_now();
+						inside.fetch_sub(1, Ordering::SeqCst);
+						drop(lock);
+					}
+				});
+			}
+		});
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_reentrant_check() {
+		use cluFlock::mode::FlockMode;
+		use cluFlock::reentrant::ReentrantFlock;
+
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_reentrant_check"));
+		let path = file.as_path();
+
+		let exclusive = ReentrantFlock::wait_exclusive_lock(path).unwrap();
+		let exclusive2 = ReentrantFlock::try_exclusive_lock(path).unwrap();
+		let shared = ReentrantFlock::try_shared_lock(path).unwrap();
+		assert_eq!(shared.held_mode(), FlockMode::Exclusive);
+		assert_eq!(exclusive.count(), 3);
+
+		// The inner guards do not unlock the outer one.
+		drop(shared);
+		drop(exclusive2);
+		if let Ok(a) = SharedFlock::try_lock(&*file) {
+			panic!("Strange behavior, the file is already locked, {:?}", a);
+		}
+		std::thread::scope(|s| {
+			s.spawn(|| match ReentrantFlock::try_shared_lock(path) {
+				Ok(a) => panic!("Strange behavior, the file is already locked, {:?}", a),
+				Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::WouldBlock),
+			});
+		});
+		drop(exclusive);
+		match ExclusiveFlock::try_lock(&*file) {
+			Ok(a) => drop(a),
+			Err(e) => panic!("Strange behavior, failed to make a lock, {:?}", e),
+		}
+
+		let shared = ReentrantFlock::wait_shared_lock(path).unwrap();
+		let shared2 = ReentrantFlock::wait_shared_lock(path).unwrap();
+		assert_eq!(shared2.count(), 2);
+		match ReentrantFlock::wait_exclusive_lock(path) {
+			Ok(a) => panic!(
+				"Strange behavior, the exclusive lock inside a shared one, {:?}",
+				a
+			),
+			Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::Deadlock),
+		}
+		assert_eq!(shared.count(), 2);
+		drop(shared2);
+		drop(shared);
+		assert!(ReentrantFlock::try_exclusive_lock(path).is_ok());
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_flock_set_check() {
+		use cluFlock::mode::Exclusive;
+		use cluFlock::mode::Shared;
+		use cluFlock::set::FlockSet;
+		use std::os::unix::fs::MetadataExt;
+
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_flock_set_check"));
+		let file2 = AutoRemoveFile::file_create(Path::new("./del_unix_flock_set_check2"));
+		let file3 = AutoRemoveFile::file_create(Path::new("./del_unix_flock_set_check3"));
+		let reopen = |file: &AutoRemoveFile| File::open(file.as_path()).unwrap();
+
+		let set = FlockSet::try_lock_all([&*file, &*file2, &*file3], Exclusive).unwrap();
+		assert_eq!(set.len(), 3);
+		for file in [&file, &file2, &file3] {
+			if let Ok(a) = SharedFlock::try_lock(reopen(file)) {
+				panic!("Strange behavior, the file is already locked, {:?}", a);
+			}
+		}
+		drop(set);
+
+		// All or nothing, the files are returned in the original order.
+		let exclusive = ExclusiveFlock::try_lock(reopen(&file2)).unwrap();
+		let e = FlockSet::try_lock_all([reopen(&file), reopen(&file2), reopen(&file3)], Shared)
+			.unwrap_err();
+		assert!(e.is_would_block());
+		let data = e.into_data();
+		assert_eq!(data.len(), 3);
+		assert_eq!(
+			data[1].metadata().unwrap().ino(),
+			file2.metadata().unwrap().ino()
+		);
+		for file in [&file, &file3] {
+			ExclusiveFlock::try_lock(reopen(file)).unwrap();
+		}
+		drop(exclusive);
+
+		let e = FlockSet::try_lock_all([&*file, &*file], Exclusive).unwrap_err();
+		assert_eq!(e.as_err().kind(), std::io::ErrorKind::InvalidInput);
+
+		// Opposite orders do not wait for each other in a cycle.
+		std::thread::scope(|s| {
+			for i in 0..4 {
+				let (file, file2, file3) = (&file, &file2, &file3);
+				s.spawn(move || {
+					for _ in 0..100 {
+						let files = match i % 2 {
+							0 => [reopen(file), reopen(file2), reopen(file3)],
+							_ => [reopen(file3), reopen(file2), reopen(file)],
+						};
+						let set = FlockSet::lock_all(files, Exclusive).unwrap();
+						assert_eq!(set.unlock_data().len(), 3);
+					}
+				});
+			}
+		});
+
+		drop(file3);
+		drop(file2);
+		drop(file);
+	}
+
+	#[test]
+	fn unix_map_check() {
+		use cluFlock::element::FlockElement;
+		use std::os::unix::io::AsRawFd;
+		use std::os::unix::io::RawFd;
+
+		#[derive(Debug)]
+		struct State {
+			file: File,
+			name: String,
+			items: Vec<u32>,
+		}
+
+		impl FlockElement for State {
+			type FilePtr = RawFd;
+
+			fn as_file_ptr(&self) -> Self::FilePtr {
+				self.file.as_raw_fd()
+			}
+		}
+
+		let file = AutoRemoveFile::file_create(Path::new("./del_unix_map_check"));
+		let reopen = || File::open(file.as_path()).unwrap();
+		let mut state = State {
+			file: reopen(),
+			name: String::from("state"),
+			items: Vec::new(),
+		};
+
+		// The mapped guard keeps the lock.
+		let lock = ExclusiveFlock::wait_lock(&mut state).unwrap();
+		let mut items = lock.map(|state| &mut state.items);
+		items.push(1);
+		if let Ok(a) = SharedFlock::try_lock(reopen()) {
+			panic!("Strange behavior, the file is already locked, {:?}", a);
+		}
+		drop(items);
+		assert_eq!(state.items, [1]);
+		ExclusiveFlock::try_lock(reopen()).unwrap();
+
+		// The original guard is returned if there is no part.
+		let lock = SharedFlock::wait_lock(Box::new(state)).unwrap();
+		let lock = match lock.try_map(|state| state.items.get(1)) {
+			Ok(a) => panic!("Strange behavior, there is no such item, {:?}", a),
+			Err(lock) => lock,
+		};
+		let name = lock.try_map(|state| Some(state.name.as_str())).unwrap();
+		assert_eq!(name.as_inner(), "state");
+		if let Ok(a) = ExclusiveFlock::try_lock(reopen()) {
+			panic!("Strange behavior, the file is already locked, {:?}", a);
+		}
+		let lock = name.into_lock();
+		assert_eq!(lock.as_inner().items, [1]);
+		lock.unlock().unwrap();
+		ExclusiveFlock::try_lock(reopen()).unwrap();
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_io_forwarding_check() {
+		use std::io::BufRead;
+		use std::io::BufReader;
+		use std::io::IsTerminal;
+		use std::io::Read;
+		use std::io::Seek;
+		use std::io::Write;
+
+		fn write_lines(mut w: impl Write) {
+			w.write_all(b"first\nsecond\n").unwrap();
+		}
+
+		fn read_all(mut r: impl Read + Seek) -> String {
+			let mut data = String::new();
+			r.rewind().unwrap();
+			r.read_to_string(&mut data).unwrap();
+
+			data
+		}
+
+		let file = AutoRemoveFile::file_create_rw(Path::new("./del_unix_io_forwarding_check"));
+		let reopen = || File::open(file.as_path()).unwrap();
+		let reopen_rw = || {
+			OpenOptions::new()
+				.read(true)
+				.write(true)
+				.open(file.as_path())
+				.unwrap()
+		};
+
+		// The guards are passed to generic code directly.
+		let mut exclusive = ExclusiveFlock::wait_lock(reopen_rw()).unwrap();
+		write_lines(&mut exclusive);
+		write_lines(&exclusive);
+		assert_eq!(read_all(&exclusive), "first\nsecond\nfirst\nsecond\n");
+		assert!(!exclusive.is_terminal());
+		drop(exclusive);
+
+		let mut shared = SharedFlock::wait_lock(BufReader::new(reopen())).unwrap();
+		let lines: Vec<String> = (&mut shared).lines().map(Result::unwrap).collect();
+		assert_eq!(lines, ["first", "second", "first", "second"]);
+		drop(shared);
+
+		let mut range = ExclusiveFlockRange::wait_lock_range(reopen_rw(), 0..6usize).unwrap();
+		range.rewind().unwrap();
+		write_lines(&mut range);
+		assert_eq!(read_all(&range), "first\nsecond\nfirst\nsecond\n");
+		let mut copy = Vec::new();
+		std::io::copy(&mut &range, &mut copy).unwrap();
+		assert!(copy.is_empty());
+		assert!(!range.is_terminal());
+		drop(range);
+
+		let range = SharedFlockRange::wait_lock_range(reopen(), 0..6usize).unwrap();
+		assert_eq!(read_all(range), "first\nsecond\nfirst\nsecond\n");
+
+		drop(file);
+	}
+
+	#[test]
+	fn unix_lock_file_check() {
+		use cluFlock::lock_file::LockFileError;
+		use cluFlock::lock_file::LockFileTransaction;
+		use std::io::Write;
+
+		let path = Path::new("./del_unix_lock_file_check");
+		let lock_path = Path::new("./del_unix_lock_file_check.lock");
+		std::fs::write(path, b"old").unwrap();
+
+		let mut transaction = LockFileTransaction::begin(path)
+			.unwrap()
+			.sync_parent_dir(true);
+		assert_eq!(transaction.as_lock_path(), lock_path);
+		match LockFileTransaction::begin(path) {
+			Err(LockFileError::Held) => {}
+			a => panic!("Strange behavior, the lock file is already held, {:?}", a),
+		}
+		let code = fork_exit_code(|| match LockFileTransaction::begin(path) {
+			Err(LockFileError::Held) => 0,
+			_ => 1,
+		});
+		assert_eq!(code, 0);
+
+		transaction.write_all(b"new").unwrap();
+		assert_eq!(std::fs::read(path).unwrap(), b"old");
+		transaction.commit().unwrap();
+		assert_eq!(std::fs::read(path).unwrap(), b"new");
+		assert!(!lock_path.exists());
+
+		// Rollback and drop do not change the file.
+		let mut transaction = LockFileTransaction::begin(path).unwrap();
+		transaction.write_all(b"rollback").unwrap();
+		transaction.rollback().unwrap();
+		let mut transaction = LockFileTransaction::begin(path).unwrap();
+		transaction.write_all(b"drop").unwrap();
+		drop(transaction);
+		assert_eq!(std::fs::read(path).unwrap(), b"new");
+		assert!(!lock_path.exists());
+
+		// The lock file of an ended process.
+		std::fs::write(lock_path, b"").unwrap();
+		match LockFileTransaction::begin(path) {
+			Err(e @ LockFileError::Stale) => assert!(e.is_held()),
+			a => panic!("Strange behavior, the lock file is stale, {:?}", a),
+		}
+		std::fs::remove_file(lock_path).unwrap();
+		std::fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn unix_append_log_check() {
+		use cluFlock::append_log::FsyncPolicy;
+		use cluFlock::append_log::LockedAppender;
+		use cluFlock::append_log::LockedLogReader;
+		use cluFlock::append_log::RecordFraming;
+		use std::io::ErrorKind;
+		use std::io::Write;
+
+		for framing in [
+			RecordFraming::LengthPrefixed,
+			RecordFraming::NewlineDelimited,
+		] {
+			let path = Path::new("./del_unix_append_log_check");
+			let read_all = || {
+				let mut reader = LockedLogReader::open(path, framing).unwrap();
+				let records: Vec<Vec<u8>> = reader.by_ref().map(Result::unwrap).collect();
+
+				(records, reader.is_torn())
+			};
+
+			let mut appender = LockedAppender::open(path, framing)
+				.unwrap()
+				.fsync_policy(FsyncPolicy::Always);
+			appender.append(b"first").unwrap();
+			appender.append(b"").unwrap();
+			assert_eq!(std::fs::metadata(path).unwrap().len(), 0); // batched
+			appender.flush().unwrap();
+			if framing == RecordFraming::NewlineDelimited {
+				assert!(appender.append(b"a\nb").is_err());
+			}
+
+			// Another writer, and a writer that crashed in the middle of a record.
+			let code = fork_exit_code(|| {
+				let mut appender = LockedAppender::open(path, framing).unwrap().batch_size(0);
+				appender.append(b"child").unwrap();
+				0
+			});
+			assert_eq!(code, 0);
+			std::fs::OpenOptions::new()
+				.append(true)
+				.open(path)
+				.unwrap()
+				.write_all(&[5, 0, 0])
+				.unwrap();
+			assert_eq!(
+				read_all(),
+				(vec![b"first".to_vec(), Vec::new(), b"child".to_vec()], true)
+			);
+
+			// The torn tail is cut off by the next write.
+			appender.append(b"last").unwrap();
+			drop(appender);
+			assert_eq!(
+				read_all(),
+				(
+					vec![
+						b"first".to_vec(),
+						Vec::new(),
+						b"child".to_vec(),
+						b"last".to_vec()
+					],
+					false
+				)
+			);
+
+			std::fs::remove_file(path).unwrap();
+		}
+
+		// A damaged record in the middle is not cut off.
+		let path = Path::new("./del_unix_append_log_check");
+		let framing = RecordFraming::LengthPrefixed;
+		let mut appender = LockedAppender::open(path, framing).unwrap();
+		for a in [b"rec0", b"rec1", b"rec2", b"rec3"] {
+			appender.append(a).unwrap();
+		}
+		drop(appender);
+		let mut data = std::fs::read(path).unwrap();
+		data[12 + 4] ^= 1; // the data of the second record
+		std::fs::write(path, &data).unwrap();
+
+		let mut appender = LockedAppender::open(path, framing).unwrap();
+		appender.append(b"last").unwrap();
+		match appender.flush() {
+			Err(e) if e.kind() == ErrorKind::InvalidData => {}
+			a => panic!("Strange behavior, the log is damaged, {:?}", a),
+		}
+		assert_eq!(std::fs::read(path).unwrap(), data);
+		let mut reader = LockedLogReader::open(path, framing).unwrap();
+		assert_eq!(reader.next().unwrap().unwrap(), b"rec0");
+		match reader.next() {
+			Some(Err(e)) if e.kind() == ErrorKind::InvalidData => {}
+			a => panic!("Strange behavior, the log is damaged, {:?}", a),
+		}
+		assert!(reader.next().is_none());
+		assert!(!reader.is_torn());
+		drop(reader);
+
+		drop(appender); // the batch is not written
+		assert_eq!(std::fs::read(path).unwrap(), data);
+
+		// A damaged last record of full length is not a torn tail.
+		data[12 + 4] ^= 1; // restore the second record
+		let last = data.len() - 12 + 4;
+		data[last] ^= 1; // the data of the last record
+		std::fs::write(path, &data).unwrap();
+
+		let mut appender = LockedAppender::open(path, framing).unwrap();
+		appender.append(b"last").unwrap();
+		match appender.flush() {
+			Err(e) if e.kind() == ErrorKind::InvalidData => {}
+			a => panic!("Strange behavior, the log is damaged, {:?}", a),
+		}
+		assert_eq!(std::fs::read(path).unwrap(), data);
+		let mut reader = LockedLogReader::open(path, framing).unwrap();
+		for a in [b"rec0", b"rec1", b"rec2"] {
+			assert_eq!(reader.next().unwrap().unwrap(), a);
+		}
+		match reader.next() {
+			Some(Err(e)) if e.kind() == ErrorKind::InvalidData => {}
+			a => panic!("Strange behavior, the log is damaged, {:?}", a),
+		}
+		assert!(reader.next().is_none());
+		assert!(!reader.is_torn());
+		drop(reader);
+
+		drop(appender); // the batch is not written
+		assert_eq!(std::fs::read(path).unwrap(), data);
+		std::fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn unix_locked_state_check() {
+		use cluFlock::state::LockedState;
+		use cluFlock::state::StateCodec;
+		use std::io::Error;
+		use std::io::ErrorKind;
+
+		struct Lines;
+
+		impl StateCodec<Vec<String>> for Lines {
+			fn encode(&self, state: &Vec<String>, out: &mut Vec<u8>) -> Result<(), Error> {
+				for a in state {
+					out.extend_from_slice(a.as_bytes());
+					out.push(b'\n');
+				}
+				Ok(())
+			}
+
+			fn decode(&self, data: &[u8]) -> Result<Vec<String>, Error> {
+				let data =
+					std::str::from_utf8(data).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+				Ok(data.lines().map(String::from).collect())
+			}
+		}
+
+		let path = Path::new("./del_unix_locked_state_check");
+		let state = LockedState::new(path, Lines);
+		assert_eq!(state.get().unwrap(), Vec::<String>::new()); // no file yet
+		assert!(!state.as_lock_path().exists()); // a read creates nothing
+
+		state.update(|a| a.push("parent".into())).unwrap();
+		let code = fork_exit_code(|| {
+			let state = LockedState::new(path, Lines);
+			for _ in 0..10 {
+				state.update(|a| a.push("child".into())).unwrap();
+			}
+			0
+		});
+		for _ in 0..10 {
+			state.update(|a| a.push("parent".into())).unwrap();
+		}
+		assert_eq!(code, 0);
+
+		let lines = state.get().unwrap();
+		assert_eq!(lines.len(), 21); // no update is lost
+		assert_eq!(lines.iter().filter(|a| *a == "child").count(), 10);
+
+		// A failed update does not write the state.
+		let result: Result<(), Error> = state.try_update(|a| {
+			a.clear();
+			Err(Error::other("cancel"))
+		});
+		assert!(result.is_err());
+		assert_eq!(state.get().unwrap().len(), 21);
+
+		std::fs::write(path, [0xff]).unwrap();
+		match state.get() {
+			Err(e) if e.kind() == ErrorKind::InvalidData => {}
+			a => panic!("Strange behavior, the state is damaged, {:?}", a),
+		}
+
+		std::fs::remove_file(path).unwrap();
+		std::fs::remove_file(state.as_lock_path()).unwrap();
+	}
 }